@@ -0,0 +1,190 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, IsTerminal};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use calc_core::{AngleMode, Backend, CalcError, Calculator};
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+const USAGE: &str = "Usage: calc [--backend NAME] [--deg] [EXPRESSION | --file PATH]";
+const PROMPT: &str = "> ";
+const HISTORY_FILE: &str = ".calc_history";
+const EXIT_USAGE: u8 = 1;
+const EXIT_PARSE: u8 = 2;
+const EXIT_EVALUATION: u8 = 3;
+
+enum Mode {
+    Expression(String),
+    File(String),
+    Repl,
+    Help,
+}
+
+struct Options {
+    backend: Backend,
+    angle_mode: AngleMode,
+    mode: Mode,
+}
+
+fn parse_arguments(mut arguments: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options { backend: Backend::default(), angle_mode: AngleMode::default(), mode: Mode::Repl };
+    let mut expression: Vec<String> = Vec::new();
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "-h" | "--help" => options.mode = Mode::Help,
+            "--deg" => options.angle_mode = AngleMode::Degrees,
+            "-b" | "--backend" => {
+                let name = arguments.next().ok_or("--backend needs a name")?;
+                options.backend = Backend::ALL.into_iter()
+                    .find(|backend| backend.label() == name)
+                    .ok_or_else(|| {
+                        let names = Backend::ALL.map(Backend::label).join(", ");
+                        format!("Unknown backend {} (expected one of {})", name, names)
+                    })?;
+            }
+            "-f" | "--file" => {
+                let path = arguments.next().ok_or("--file needs a path")?;
+                options.mode = Mode::File(path);
+            }
+            _ => expression.push(argument),
+        }
+    }
+    if !expression.is_empty() {
+        if let Mode::File(_) = options.mode {
+            return Err(String::from("Cannot evaluate an expression and a file at the same time"));
+        }
+        options.mode = Mode::Expression(expression.join(" "));
+    }
+    Ok(options)
+}
+
+fn exit_code(error: &CalcError) -> u8 {
+    if error.is_parse_error() { EXIT_PARSE } else { EXIT_EVALUATION }
+}
+
+fn evaluate(calculator: &mut Calculator, line: &str) -> Result<(), CalcError> {
+    if let Some(value) = calculator.calculate(line)? {
+        println!("{}", value);
+    }
+    Ok(())
+}
+
+fn report(line: &str, error: &CalcError) {
+    let start = error.span().start;
+    let column = line.get(..start).map(|prefix| prefix.chars().count()).unwrap_or(start);
+    eprintln!("{}", line);
+    eprintln!("{}^", " ".repeat(column));
+    eprintln!("error: {}", error);
+}
+
+fn run_expression(calculator: &mut Calculator, expression: &str) -> ExitCode {
+    match evaluate(calculator, expression) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            report(expression, &error);
+            ExitCode::from(exit_code(&error))
+        }
+    }
+}
+
+fn run_lines(calculator: &mut Calculator, reader: impl BufRead, name: &str) -> ExitCode {
+    let mut code = None;
+    for (index, line) in reader.lines().enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(error) => {
+                eprintln!("{}: {}", name, error);
+                return ExitCode::from(EXIT_USAGE);
+            }
+        };
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Err(error) = evaluate(calculator, trimmed) {
+            eprintln!("{}:{}: {}", name, index + 1, error);
+            code.get_or_insert(exit_code(&error));
+        }
+    }
+    code.map_or(ExitCode::SUCCESS, ExitCode::from)
+}
+
+fn history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(HISTORY_FILE))
+}
+
+fn run_repl(calculator: &mut Calculator) -> ExitCode {
+    let mut editor = match DefaultEditor::new() {
+        Ok(editor) => editor,
+        Err(error) => {
+            eprintln!("Cannot start the line editor: {}", error);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+    let history = history_path();
+    if let Some(path) = &history {
+        let _ = editor.load_history(path);
+    }
+    loop {
+        match editor.readline(PROMPT) {
+            Ok(line) => {
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                if line == "exit" || line == "quit" {
+                    break;
+                }
+                let _ = editor.add_history_entry(line);
+                if let Err(error) = evaluate(calculator, line) {
+                    report(line, &error);
+                }
+            }
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(error) => {
+                eprintln!("{}", error);
+                return ExitCode::from(EXIT_USAGE);
+            }
+        }
+    }
+    if let Some(path) = &history {
+        if let Err(error) = editor.save_history(path) {
+            eprintln!("Cannot save history to {}: {}", path.display(), error);
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn main() -> ExitCode {
+    let options = match parse_arguments(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n{}", message, USAGE);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+    let mut calculator = Calculator::default();
+    calculator.set_backend(options.backend);
+    calculator.set_angle_mode(options.angle_mode);
+    match options.mode {
+        Mode::Help => {
+            println!("{}", USAGE);
+            println!("Exit codes: 0 success, {} usage or I/O error, {} parse error, {} evaluation error", EXIT_USAGE, EXIT_PARSE, EXIT_EVALUATION);
+            ExitCode::SUCCESS
+        }
+        Mode::Expression(expression) => run_expression(&mut calculator, &expression),
+        Mode::File(path) => match File::open(&path) {
+            Ok(file) => run_lines(&mut calculator, BufReader::new(file), &path),
+            Err(error) => {
+                eprintln!("Cannot open {}: {}", path, error);
+                ExitCode::from(EXIT_USAGE)
+            }
+        },
+        Mode::Repl if io::stdin().is_terminal() => run_repl(&mut calculator),
+        Mode::Repl => run_lines(&mut calculator, io::stdin().lock(), "<stdin>"),
+    }
+}
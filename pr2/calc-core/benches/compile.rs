@@ -0,0 +1,39 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use calc_core::{Calculator, Value};
+
+const FORMULAS: [&str; 3] = ["x^2 * sin(x) + 3*x - 1/(x + 1)", "sqrt(x^2 + 1) * e^(-x/10)", "max(x, 2) + (x - 1)! / 3"];
+const ITERATIONS: usize = 100_000;
+
+fn measure(mut run: impl FnMut(f64) -> f64) -> Duration {
+    let start = Instant::now();
+    let mut total = 0.0;
+    for index in 0..ITERATIONS {
+        total += run(black_box((index % 10) as f64 + 0.5));
+    }
+    black_box(total);
+    start.elapsed()
+}
+
+fn per_call(duration: Duration) -> f64 {
+    duration.as_nanos() as f64 / ITERATIONS as f64
+}
+
+fn main() {
+    let mut calculator = Calculator::default();
+    println!("{:<34} {:>12} {:>12} {:>12} {:>8}", "formula", "parse ns", "tree ns", "compiled ns", "speedup");
+    for formula in FORMULAS {
+        let parsed = measure(|x| {
+            calculator.set_variable("x", Value::Float(x)).expect("x is a valid name");
+            calculator.evaluate_expression(formula).map_or(f64::NAN, |value| value.to_f64())
+        });
+        let function = calculator.compile_function(formula, &["x"]).expect("formula compiles");
+        let tree = measure(|x| calculator.call_function(&function, vec![x]).unwrap_or(f64::NAN));
+        calculator.remove_variable("x");
+        let compiled = calculator.compile(formula).expect("formula compiles");
+        let fast = measure(|x| compiled.eval(&[x]).unwrap_or(f64::NAN));
+        println!("{:<34} {:>12.1} {:>12.1} {:>12.1} {:>7.1}x",
+            formula, per_call(parsed), per_call(tree), per_call(fast), per_call(parsed) / per_call(fast));
+    }
+}
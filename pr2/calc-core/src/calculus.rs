@@ -0,0 +1,197 @@
+use super::error::CalcError;
+use super::functions::Arity;
+
+pub(crate) const DEFAULT_VARIABLE: &str = "x";
+const DEFAULT_GUESS: f64 = 1.0;
+const BRACKET_STEPS: usize = 60;
+const BISECTION_STEPS: usize = 200;
+const NEWTON_STEPS: usize = 100;
+const INTEGRATION_DEPTH: usize = 50;
+const INTEGRATION_TOLERANCE: f64 = 1e-10;
+const MAX_EVALUATIONS: usize = 1_000_000;
+const DERIVATIVE_STEPS: usize = 10;
+pub(crate) const MAX_TERMS: f64 = 1_000_000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Calculus {
+    Solve,
+    Integrate,
+    Derive,
+    Sum,
+}
+
+impl Calculus {
+    pub(crate) fn from_name(name: &str) -> Option<Calculus> {
+        match name {
+            "solve" => Some(Calculus::Solve),
+            "integrate" => Some(Calculus::Integrate),
+            "derive" => Some(Calculus::Derive),
+            "sum" => Some(Calculus::Sum),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn arity(self) -> Arity {
+        match self {
+            Calculus::Solve => Arity::Range(2, 3),
+            Calculus::Integrate => Arity::Range(3, 4),
+            Calculus::Derive => Arity::Exact(3),
+            Calculus::Sum => Arity::Exact(4),
+        }
+    }
+
+    pub(crate) fn variable_index(self, arguments: usize) -> Option<usize> {
+        match self {
+            Calculus::Solve | Calculus::Derive => Some(1),
+            Calculus::Integrate if arguments == 4 => Some(1),
+            Calculus::Integrate => None,
+            Calculus::Sum => Some(0),
+        }
+    }
+}
+
+type Sampler<'a> = dyn FnMut(f64) -> Result<f64, CalcError> + 'a;
+
+pub(crate) fn solve(f: &mut Sampler, guess: Option<f64>) -> Result<Option<f64>, CalcError> {
+    let guess = guess.unwrap_or(DEFAULT_GUESS);
+    let at_guess = f(guess)?;
+    if at_guess == 0.0 {
+        return Ok(Some(guess));
+    }
+    let mut step = 1e-3 * guess.abs().max(1.0);
+    for _ in 0..BRACKET_STEPS {
+        for other in [guess + step, guess - step] {
+            let Ok(value) = f(other) else {
+                continue;
+            };
+            if value == 0.0 {
+                return Ok(Some(other));
+            }
+            if value.is_finite() && value.signum() != at_guess.signum() {
+                if let Some(root) = bisect(f, guess, other, at_guess, value) {
+                    return Ok(Some(root));
+                }
+            }
+        }
+        step *= 2.0;
+    }
+    Ok(newton(f, guess))
+}
+
+fn bisect(f: &mut Sampler, mut left: f64, mut right: f64, left_value: f64, right_value: f64) -> Option<f64> {
+    let scale = left_value.abs().max(right_value.abs());
+    for _ in 0..BISECTION_STEPS {
+        let middle = (left + right) / 2.0;
+        if middle == left || middle == right {
+            break;
+        }
+        let value = f(middle).ok()?;
+        if value == 0.0 {
+            return Some(middle);
+        }
+        if value.signum() == left_value.signum() {
+            left = middle;
+        }
+        else {
+            right = middle;
+        }
+    }
+    let root = (left + right) / 2.0;
+    let value = f(root).ok()?;
+    (value.abs() <= 1e-9 * scale.max(1.0)).then_some(root)
+}
+
+fn newton(f: &mut Sampler, mut x: f64) -> Option<f64> {
+    for _ in 0..NEWTON_STEPS {
+        let value = f(x).ok()?;
+        let h = 1e-7 * x.abs().max(1.0);
+        let slope = (f(x + h).ok()? - f(x - h).ok()?) / (2.0 * h);
+        if value == 0.0 {
+            return Some(x);
+        }
+        if slope == 0.0 || !slope.is_finite() {
+            return None;
+        }
+        let step = value / slope;
+        x -= step;
+        if step.abs() <= 1e-12 * x.abs().max(1.0) {
+            return (f(x).ok()?.abs() <= 1e-9).then_some(x);
+        }
+    }
+    None
+}
+
+pub(crate) fn integrate(f: &mut Sampler, a: f64, b: f64) -> Result<Option<f64>, CalcError> {
+    if !a.is_finite() || !b.is_finite() {
+        return Ok(None);
+    }
+    let (fa, fb) = (f(a)?, f(b)?);
+    let middle = (a + b) / 2.0;
+    let fm = f(middle)?;
+    let whole = simpson(a, b, fa, fm, fb);
+    let mut evaluations = 3;
+    let tolerance = INTEGRATION_TOLERANCE * whole.abs().max(1.0);
+    let result = adaptive_simpson(f, [a, middle, b], [fa, fm, fb], whole, tolerance, INTEGRATION_DEPTH, &mut evaluations)?;
+    Ok(result.filter(|value| value.is_finite()))
+}
+
+fn simpson(a: f64, b: f64, fa: f64, fm: f64, fb: f64) -> f64 {
+    (b - a) / 6.0 * (fa + 4.0 * fm + fb)
+}
+
+fn adaptive_simpson(
+    f: &mut Sampler,
+    [a, m, b]: [f64; 3],
+    [fa, fm, fb]: [f64; 3],
+    whole: f64,
+    tolerance: f64,
+    depth: usize,
+    evaluations: &mut usize,
+) -> Result<Option<f64>, CalcError> {
+    let (left_middle, right_middle) = ((a + m) / 2.0, (m + b) / 2.0);
+    let (flm, frm) = (f(left_middle)?, f(right_middle)?);
+    *evaluations += 2;
+    let left = simpson(a, m, fa, flm, fm);
+    let right = simpson(m, b, fm, frm, fb);
+    let difference = left + right - whole;
+    if difference.abs() <= 15.0 * tolerance {
+        return Ok(Some(left + right + difference / 15.0));
+    }
+    if depth == 0 || *evaluations > MAX_EVALUATIONS {
+        return Ok(None);
+    }
+    let Some(left) = adaptive_simpson(f, [a, left_middle, m], [fa, flm, fm], left, tolerance / 2.0, depth - 1, evaluations)? else {
+        return Ok(None);
+    };
+    let right = adaptive_simpson(f, [m, right_middle, b], [fm, frm, fb], right, tolerance / 2.0, depth - 1, evaluations)?;
+    Ok(right.map(|right| left + right))
+}
+
+pub(crate) fn derive(f: &mut Sampler, at: f64) -> Result<Option<f64>, CalcError> {
+    let shrink: f64 = 1.4;
+    let shrink_squared = shrink * shrink;
+    let mut h = 0.1 * at.abs().max(1.0);
+    let mut table = [[0.0; DERIVATIVE_STEPS]; DERIVATIVE_STEPS];
+    table[0][0] = (f(at + h)? - f(at - h)?) / (2.0 * h);
+    let mut result = table[0][0];
+    let mut error = f64::MAX;
+    for i in 1..DERIVATIVE_STEPS {
+        h /= shrink;
+        table[0][i] = (f(at + h)? - f(at - h)?) / (2.0 * h);
+        let mut factor = shrink_squared;
+        for j in 1..=i {
+            table[j][i] = (table[j - 1][i] * factor - table[j - 1][i - 1]) / (factor - 1.0);
+            factor *= shrink_squared;
+            let estimate = (table[j][i] - table[j - 1][i]).abs().max((table[j][i] - table[j - 1][i - 1]).abs());
+            if estimate <= error {
+                error = estimate;
+                result = table[j][i];
+            }
+        }
+        if (table[i][i] - table[i - 1][i - 1]).abs() >= 2.0 * error {
+            break;
+        }
+    }
+    let converged = result.is_finite() && error <= 1e-6 * result.abs().max(1.0);
+    Ok(converged.then_some(result))
+}
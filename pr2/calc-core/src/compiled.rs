@@ -0,0 +1,237 @@
+use super::error::CalcError;
+use super::functions::{self, AngleMode, Function};
+use super::number::NumberError;
+use super::statistics;
+use super::token::{Operator, Span, UnaryOperator};
+use super::{get_operation_result, get_unary_operation_result};
+
+const MAX_STACK: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Instruction {
+    Constant(f64),
+    Input(usize),
+    Copy(usize),
+    Unary { operator: UnaryOperator, span: Span },
+    Binary { operator: Operator, span: Span, divisor_span: Span },
+    Factorial { span: Span },
+    Call { function: Function, arguments: usize, span: Span },
+    Unwind { count: usize },
+}
+
+impl Instruction {
+    fn stack_effect(self) -> (usize, usize) {
+        match self {
+            Instruction::Constant(_) | Instruction::Input(_) | Instruction::Copy(_) => (0, 1),
+            Instruction::Unary { .. } | Instruction::Factorial { .. } => (1, 1),
+            Instruction::Binary { .. } => (2, 1),
+            Instruction::Call { arguments, .. } => (arguments, 1),
+            Instruction::Unwind { count } => (count + 1, 1),
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Program {
+    instructions: Vec<Instruction>,
+    inputs: Vec<String>,
+    depth: usize,
+}
+
+impl Program {
+    pub(crate) fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub(crate) fn input(&mut self, name: &str) -> usize {
+        match self.inputs.iter().position(|input| input == name) {
+            Some(index) => index,
+            None => {
+                self.inputs.push(name.to_string());
+                self.inputs.len() - 1
+            }
+        }
+    }
+
+    pub(crate) fn push(&mut self, instruction: Instruction, span: Span) -> Result<(), CalcError> {
+        let (popped, pushed) = instruction.stack_effect();
+        self.depth = self.depth - popped + pushed;
+        if self.depth > MAX_STACK {
+            let message = String::from("Expression is too deeply nested to compile");
+            return Err(CalcError::Domain { message, span });
+        }
+        self.instructions.push(instruction);
+        Ok(())
+    }
+
+    pub(crate) fn finish(self, source: &str, angle_mode: AngleMode) -> CompiledExpr {
+        CompiledExpr { instructions: self.instructions, variables: self.inputs, angle_mode, source: source.trim().to_string() }
+    }
+}
+
+/// A real expression compiled by [`Calculator::compile`](crate::Calculator::compile).
+/// It runs on a fixed-size stack, so a successful evaluation does not allocate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledExpr {
+    instructions: Vec<Instruction>,
+    variables: Vec<String>,
+    angle_mode: AngleMode,
+    source: String,
+}
+
+impl CompiledExpr {
+    /// Returns the input names in the order [`CompiledExpr::eval`] expects their values.
+    pub fn variables(&self) -> &[String] {
+        &self.variables
+    }
+
+    /// Returns the expression this was compiled from.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Evaluates with one value per input. A wrong number of values gives [`CalcError::Inputs`].
+    pub fn eval(&self, inputs: &[f64]) -> Result<f64, CalcError> {
+        if inputs.len() != self.variables.len() {
+            return Err(CalcError::Inputs { expected: self.variables.len(), found: inputs.len(), span: Span::new(0, self.source.len()) });
+        }
+        let mut stack = [0.0; MAX_STACK];
+        let mut top = 0;
+        for instruction in &self.instructions {
+            match *instruction {
+                Instruction::Constant(value) => {
+                    stack[top] = value;
+                    top += 1;
+                }
+                Instruction::Input(index) => {
+                    stack[top] = inputs[index];
+                    top += 1;
+                }
+                Instruction::Copy(index) => {
+                    stack[top] = stack[index];
+                    top += 1;
+                }
+                Instruction::Unary { operator, span } => {
+                    stack[top - 1] = get_unary_operation_result(stack[top - 1], operator, self.angle_mode, span)?;
+                }
+                Instruction::Binary { operator, span, divisor_span } => {
+                    top -= 1;
+                    stack[top - 1] = get_operation_result(stack[top - 1], stack[top], operator, self.angle_mode, span, divisor_span)?;
+                }
+                Instruction::Factorial { span } => {
+                    stack[top - 1] = functions::factorial(&stack[top - 1]).map_err(|error| error.at(span))?;
+                }
+                Instruction::Call { function, arguments, span } => {
+                    let result = call(function, &mut stack[top - arguments..top], self.angle_mode).map_err(|error| error.at(span))?;
+                    top -= arguments;
+                    stack[top] = result;
+                    top += 1;
+                }
+                Instruction::Unwind { count } => {
+                    stack[top - count - 1] = stack[top - 1];
+                    top -= count;
+                }
+            }
+        }
+        Ok(stack[0])
+    }
+}
+
+fn call(function: Function, arguments: &mut [f64], angle_mode: AngleMode) -> Result<f64, NumberError> {
+    match function {
+        Function::Median | Function::Mode => {
+            statistics::sort(arguments)?;
+            if function == Function::Median { statistics::sorted_median(arguments) } else { statistics::sorted_mode(arguments) }
+        }
+        Function::Percentile => {
+            let (percent, values) = arguments.split_last_mut().expect("percentile takes two arguments");
+            statistics::sort(values)?;
+            statistics::sorted_percentile(values, percent)
+        }
+        _ => function.apply(arguments, angle_mode),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AngleMode, CalcError, Calculator};
+
+    fn compiled(calculator: &Calculator, input: &str, inputs: &[f64]) -> f64 {
+        let expression = calculator.compile(input).unwrap_or_else(|error| panic!("{} gave {}", input, error));
+        expression.eval(inputs).unwrap_or_else(|error| panic!("{} gave {}", input, error))
+    }
+
+    #[test]
+    fn matches_the_interpreter() {
+        let calculator = Calculator::default();
+        for input in ["1 + 2 * 3", "-(2 - 5)^2", "2^3^2", "5! / 3", "sqrt(16) + ln(e)", "max(3, 9, 4) - min(1, -2)", "mean(1, 2, 3, 4)", "atan(1) * 4", "15% * 200"] {
+            let expected = calculator.evaluate_expression(input).unwrap().to_f64();
+            assert_eq!(compiled(&calculator, input, &[]), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn binds_inputs_in_order_of_appearance() {
+        let calculator = Calculator::default();
+        let expression = calculator.compile("y * 10 + x - y").unwrap();
+        assert_eq!(expression.variables(), ["y", "x"]);
+        assert_eq!(expression.eval(&[2.0, 5.0]).unwrap(), 23.0);
+        assert_eq!(expression.eval(&[3.0, 1.0]).unwrap(), 28.0);
+    }
+
+    #[test]
+    fn resolves_variables_and_user_functions_at_compile_time() {
+        let mut calculator = Calculator::default();
+        calculator.calculate("rate = 3").unwrap();
+        calculator.calculate("f(x) = x^2 + rate").unwrap();
+        calculator.calculate("g(a, b) = f(a) - f(b)").unwrap();
+        let expression = calculator.compile("g(u, 2) + f(1) * u").unwrap();
+        assert_eq!(expression.variables(), ["u"]);
+        assert_eq!(expression.eval(&[4.0]).unwrap(), 12.0 + 4.0 * 4.0);
+        calculator.calculate("rate = 100").unwrap();
+        assert_eq!(expression.eval(&[4.0]).unwrap(), 28.0);
+    }
+
+    #[test]
+    fn evaluates_aggregates_over_inputs() {
+        let calculator = Calculator::default();
+        let expression = calculator.compile("max(a, b, c) - min(a, b, c) + median(c, a, b) * 10 + mode(a, b, b, c)").unwrap();
+        assert_eq!(expression.variables(), ["a", "b", "c"]);
+        assert_eq!(expression.eval(&[4.0, -1.0, 9.0]).unwrap(), 10.0 + 40.0 - 1.0);
+        assert_eq!(expression.eval(&[0.0, 2.0, 1.0]).unwrap(), 2.0 + 10.0 + 2.0);
+        let spread = calculator.compile("stdev(x, y, 4) + percentile(x, 50)").unwrap();
+        let expected = calculator.evaluate_expression("stdev(2, 6, 4) + percentile(2, 50)").unwrap().to_f64();
+        assert_eq!(spread.eval(&[2.0, 6.0]).unwrap(), expected);
+    }
+
+    #[test]
+    fn keeps_the_angle_mode() {
+        let mut calculator = Calculator::default();
+        calculator.set_angle_mode(AngleMode::Degrees);
+        let expression = calculator.compile("sin(x) + cos(90°)").unwrap();
+        calculator.set_angle_mode(AngleMode::Radians);
+        assert!((expression.eval(&[30.0]).unwrap() - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn reports_errors() {
+        let calculator = Calculator::default();
+        let expression = calculator.compile("1 / (x - 1)").unwrap();
+        assert!(matches!(expression.eval(&[1.0]), Err(CalcError::DivisionByZero { .. })));
+        assert!(matches!(expression.eval(&[]), Err(CalcError::Inputs { expected: 1, found: 0, .. })));
+        assert!(matches!(calculator.compile("[1, 2] * x"), Err(CalcError::Domain { .. })));
+        assert!(matches!(calculator.compile("integrate(x^2, 0, 1)"), Err(CalcError::Domain { .. })));
+        for input in ["3 km", "2 * x + 1 m", "x in km", "x & 3", "1 << n", "~x", "2∠30"] {
+            assert!(matches!(calculator.compile(input), Err(CalcError::Domain { .. })), "{}", input);
+        }
+        let Err(error) = calculator.compile("x + 3 km") else { panic!("units compiled") };
+        assert_eq!((error.message().as_str(), error.span().start), ("Unit km cannot be compiled", 6));
+        for (input, start) in [("amort(x, 2, 3)", 0), ("1 + linreg(x, x)", 4), ("corr(x, x)", 0), ("2 * simplify(x)", 4)] {
+            let Err(error) = calculator.compile(input) else { panic!("{} compiled", input) };
+            assert!(error.message().ends_with("cannot be compiled"), "{}", error.message());
+            assert_eq!(error.span().start, start, "{}", input);
+        }
+        let nested = format!("{}1{}", "(1 + ".repeat(100), ")".repeat(100));
+        assert!(matches!(calculator.compile(&nested), Err(CalcError::Domain { .. })));
+    }
+}
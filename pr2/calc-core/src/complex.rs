@@ -0,0 +1,385 @@
+use std::cmp::Ordering;
+
+use super::functions::{AngleMode, Function};
+use super::number::{format_float, Number, NumberError, Value};
+
+const MAX_INTEGER_POWER: f64 = 1024.0;
+
+/// A value of the complex backend.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Complex {
+    /// Real part.
+    pub re: f64,
+    /// Imaginary part.
+    pub im: f64,
+}
+
+/// How complex results are formatted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ComplexDisplay {
+    /// As `a + bi`.
+    #[default]
+    Rectangular,
+    /// As `r∠θ`.
+    Polar,
+}
+
+impl Complex {
+    pub(crate) const I: Complex = Complex { re: 0.0, im: 1.0 };
+
+    fn new(re: f64, im: f64) -> Self {
+        // Adding zero turns -0.0 into +0.0, so atan2 and sqrt pick the principal branch.
+        Complex { re: re + 0.0, im: im + 0.0 }
+    }
+
+    fn real(re: f64) -> Self {
+        Complex::new(re, 0.0)
+    }
+
+    fn modulus(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    fn argument(self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    fn from_polar(modulus: f64, angle: f64) -> Self {
+        Complex::new(modulus * angle.cos(), modulus * angle.sin())
+    }
+
+    fn checked(self) -> Result<Self, NumberError> {
+        if self.re.is_nan() || self.im.is_nan() {
+            Err(NumberError::Domain(String::from("Result is not a number")))
+        }
+        else if self.re.is_infinite() || self.im.is_infinite() {
+            Err(NumberError::Overflow)
+        }
+        else {
+            Ok(self.cleaned())
+        }
+    }
+
+    fn cleaned(self) -> Self {
+        let scale = self.modulus() * 1e-15;
+        let clean = |part: f64| if part.abs() <= scale { 0.0 } else { part };
+        Complex::new(clean(self.re), clean(self.im))
+    }
+
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(self.re * other.re - self.im * other.im, self.re * other.im + self.im * other.re)
+    }
+
+    fn div(self, other: Complex) -> Complex {
+        let denominator = other.re * other.re + other.im * other.im;
+        Complex::new(
+            (self.re * other.re + self.im * other.im) / denominator,
+            (self.im * other.re - self.re * other.im) / denominator,
+        )
+    }
+
+    fn exp(self) -> Complex {
+        Complex::from_polar(self.re.exp(), self.im)
+    }
+
+    fn ln(self) -> Complex {
+        Complex::new(self.modulus().ln(), self.argument())
+    }
+
+    fn sqrt(self) -> Complex {
+        if self.is_real() && self.re >= 0.0 {
+            return Complex::real(self.re.sqrt());
+        }
+        let modulus = self.modulus();
+        let re = ((modulus + self.re) / 2.0).sqrt();
+        let im = ((modulus - self.re) / 2.0).sqrt();
+        Complex::new(re, if self.im < 0.0 { -im } else { im })
+    }
+
+    fn sin(self) -> Complex {
+        Complex::new(self.re.sin() * self.im.cosh(), self.re.cos() * self.im.sinh())
+    }
+
+    fn cos(self) -> Complex {
+        Complex::new(self.re.cos() * self.im.cosh(), -self.re.sin() * self.im.sinh())
+    }
+
+    fn asin(self) -> Complex {
+        let root = Complex::real(1.0).sub(self.mul(self)).sqrt();
+        Complex::I.mul(self).add(root).ln().mul(Complex::new(0.0, -1.0))
+    }
+
+    fn acos(self) -> Complex {
+        Complex::real(std::f64::consts::FRAC_PI_2).sub(self.asin())
+    }
+
+    fn atan(self) -> Complex {
+        let i = Complex::I;
+        let ratio = i.add(self).div(i.sub(self));
+        ratio.ln().mul(Complex::new(0.0, -0.5))
+    }
+
+    fn powi(self, exponent: i64) -> Complex {
+        let mut result = Complex::real(1.0);
+        let mut base = if exponent < 0 { Complex::real(1.0).div(self) } else { self };
+        let mut exponent = exponent.unsigned_abs();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(base);
+            }
+            base = base.mul(base);
+            exponent >>= 1;
+        }
+        result
+    }
+
+    pub(crate) fn format(self, display: ComplexDisplay) -> String {
+        if display == ComplexDisplay::Polar && !self.is_real() {
+            return format!("{}∠{}°", format_float(self.modulus()), format_float(self.argument().to_degrees()));
+        }
+        let imaginary = if self.im.abs() == 1.0 { String::from("i") } else { format!("{}i", format_float(self.im.abs())) };
+        match (self.re == 0.0, self.im == 0.0) {
+            (_, true) => format_float(self.re),
+            (true, false) if self.im < 0.0 => format!("-{}", imaginary),
+            (true, false) => imaginary,
+            (false, false) if self.im < 0.0 => format!("{} - {}", format_float(self.re), imaginary),
+            (false, false) => format!("{} + {}", format_float(self.re), imaginary),
+        }
+    }
+
+    pub(crate) fn apply(function: Function, arguments: &[Complex], angle_mode: AngleMode) -> Result<Option<Complex>, NumberError> {
+        let x = arguments[0];
+        let to_radians = |z: Complex| match angle_mode {
+            AngleMode::Radians => z,
+            AngleMode::Degrees => z.mul(Complex::real(std::f64::consts::PI / 180.0)),
+        };
+        let from_radians = |z: Complex| match angle_mode {
+            AngleMode::Radians => z,
+            AngleMode::Degrees => z.mul(Complex::real(180.0 / std::f64::consts::PI)),
+        };
+        let nonzero = |z: Complex| {
+            if z.is_zero() {
+                Err(NumberError::Domain(String::from("Logarithm of zero")))
+            }
+            else {
+                Ok(z)
+            }
+        };
+        let result = match function {
+            Function::Sin => to_radians(x).sin(),
+            Function::Cos => to_radians(x).cos(),
+            Function::Tan => {
+                let cos = to_radians(x).cos();
+                if cos.modulus() < 1e-12 {
+                    return Err(NumberError::Domain(String::from("Tangent is undefined")));
+                }
+                to_radians(x).sin().div(cos)
+            }
+            Function::Asin => from_radians(x.asin()),
+            Function::Acos => from_radians(x.acos()),
+            Function::Atan => {
+                if x == Complex::I || x == Complex::new(0.0, -1.0) {
+                    return Err(NumberError::Domain(String::from("Arctangent is undefined at ±i")));
+                }
+                from_radians(x.atan())
+            }
+            Function::Sqrt => x.sqrt(),
+            Function::Ln => nonzero(x)?.ln(),
+            Function::Log10 => nonzero(x)?.ln().div(Complex::real(std::f64::consts::LN_10)),
+            Function::Log => {
+                let base = nonzero(x)?.ln();
+                if base.is_zero() {
+                    return Err(NumberError::Domain(String::from("Logarithm base must be positive and not 1")));
+                }
+                nonzero(arguments[1])?.ln().div(base)
+            }
+            Function::Abs => Complex::real(x.modulus()),
+            Function::Arg => from_radians(Complex::real(x.argument())),
+            Function::Conj => Complex::new(x.re, -x.im),
+            Function::Re => Complex::real(x.re),
+            Function::Im => Complex::real(x.im),
+            Function::Floor => x.floor(),
+            Function::Ceil => x.ceil(),
+            Function::Round => x.round(),
+            _ => return Ok(None),
+        };
+        result.checked().map(Some)
+    }
+}
+
+impl PartialOrd for Complex {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self.is_real() && other.is_real() { self.re.partial_cmp(&other.re) } else { None }
+    }
+}
+
+impl Number for Complex {
+    fn parse_literal(literal: &str) -> Option<Self> {
+        f64::parse_literal(literal).map(Complex::real)
+    }
+
+    fn from_f64(value: f64) -> Option<Self> {
+        value.is_finite().then(|| Complex::real(value))
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Complex(value) => Some(*value),
+            Value::Quantity(quantity) => Self::from_value(&quantity.base),
+            value => f64::from_value(value).map(Complex::real),
+        }
+    }
+
+    fn to_f64(&self) -> f64 {
+        self.re
+    }
+
+    fn zero() -> Self {
+        Complex::real(0.0)
+    }
+
+    fn one() -> Self {
+        Complex::real(1.0)
+    }
+
+    fn checked_add(&self, other: &Self) -> Result<Self, NumberError> {
+        Complex::add(*self, *other).checked()
+    }
+
+    fn checked_sub(&self, other: &Self) -> Result<Self, NumberError> {
+        Complex::sub(*self, *other).checked()
+    }
+
+    fn checked_mul(&self, other: &Self) -> Result<Self, NumberError> {
+        Complex::mul(*self, *other).checked()
+    }
+
+    fn checked_div(&self, other: &Self) -> Result<Self, NumberError> {
+        Complex::div(*self, *other).checked()
+    }
+
+    fn checked_pow(&self, exponent: &Self) -> Result<Self, NumberError> {
+        if exponent.is_integer() && exponent.re.abs() <= MAX_INTEGER_POWER {
+            return self.powi(exponent.re as i64).checked();
+        }
+        if self.is_real() && self.re >= 0.0 && exponent.is_real() {
+            return Complex::real(self.re.powf(exponent.re)).checked();
+        }
+        if self.is_zero() {
+            if exponent.re > 0.0 {
+                return Ok(Complex::zero());
+            }
+            return Err(NumberError::Domain(String::from("Zero to a power with a non-positive real part")));
+        }
+        exponent.mul(self.ln()).exp().checked()
+    }
+
+    fn negate(&self) -> Self {
+        Complex::new(-self.re, -self.im)
+    }
+
+    fn abs(&self) -> Self {
+        Complex::real(self.modulus())
+    }
+
+    fn floor(&self) -> Self {
+        Complex::new(self.re.floor(), self.im.floor())
+    }
+
+    fn ceil(&self) -> Self {
+        Complex::new(self.re.ceil(), self.im.ceil())
+    }
+
+    fn round(&self) -> Self {
+        Complex::new(self.re.round(), self.im.round())
+    }
+
+    fn checked_polar(&self, angle: &Self, angle_mode: AngleMode) -> Result<Self, NumberError> {
+        if !angle.is_real() {
+            return Err(NumberError::Domain(String::from("Polar angle must be real")));
+        }
+        let radians = match angle_mode {
+            AngleMode::Radians => angle.re,
+            AngleMode::Degrees => angle.re.to_radians(),
+        };
+        Complex::mul(*self, Complex::from_polar(1.0, radians)).checked()
+    }
+
+    fn check_compatible(&self, other: &Self) -> Result<(), NumberError> {
+        if self.is_real() && other.is_real() {
+            Ok(())
+        }
+        else {
+            Err(NumberError::Domain(String::from("Complex numbers cannot be compared")))
+        }
+    }
+
+    fn imaginary_unit() -> Option<Self> {
+        Some(Complex::I)
+    }
+
+    fn apply_function(function: Function, arguments: &[Self], angle_mode: AngleMode) -> Option<Result<Self, NumberError>> {
+        Complex::apply(function, arguments, angle_mode).transpose()
+    }
+
+    fn is_real(&self) -> bool {
+        self.im == 0.0
+    }
+
+    fn is_zero(&self) -> bool {
+        self.re == 0.0 && self.im == 0.0
+    }
+
+    fn is_integer(&self) -> bool {
+        self.is_real() && self.re.fract() == 0.0
+    }
+
+    fn is_negative(&self) -> bool {
+        self.is_real() && self.re < 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Backend, Calculator, ComplexDisplay, DisplayOptions};
+
+    fn format(calculator: &Calculator, input: &str, complex: ComplexDisplay) -> String {
+        let options = DisplayOptions { complex, ..DisplayOptions::default() };
+        calculator.evaluate_expression(input).unwrap_or_else(|error| panic!("{} gave {}", input, error)).format(&options)
+    }
+
+    #[test]
+    fn uses_principal_values_for_negative_reals() {
+        let mut calculator = Calculator::default();
+        calculator.set_backend(Backend::Complex);
+        for (input, expected) in [
+            ("sqrt(-4)", "2i"),
+            ("(-4)^0.5", "2i"),
+            ("(-1)^0.5", "i"),
+            ("ln(-1)", "3.14159265358979i"),
+            ("ln(0-1)", "3.14159265358979i"),
+            ("arg(-1)", "3.14159265358979"),
+            ("(3+4i)*(1-2i)", "11 - 2i"),
+            ("-(2i)", "-2i"),
+        ] {
+            assert_eq!(format(&calculator, input, ComplexDisplay::Rectangular), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn formats_polar_form() {
+        let mut calculator = Calculator::default();
+        calculator.set_backend(Backend::Complex);
+        for (input, expected) in [("1+i", "1.4142135623731∠45°"), ("-2i", "2∠-90°"), ("-1", "-1"), ("3+4i", "5∠53.130102354156°")] {
+            assert_eq!(format(&calculator, input, ComplexDisplay::Polar), expected, "{}", input);
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use std::fmt;
+
+use super::functions::Arity;
+use super::token::Span;
+
+/// Why the input could not be split into tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LexErrorKind {
+    /// A character that is not part of the syntax.
+    UnexpectedCharacter(char),
+    /// A number literal the backend cannot represent.
+    InvalidNumber,
+}
+
+/// Why the tokens do not form a valid statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntaxErrorKind {
+    /// A token where it is not allowed.
+    UnexpectedToken,
+    /// A parenthesis or bracket without its partner.
+    UnbalancedParenthesis,
+    /// Parentheses with nothing inside.
+    EmptyParentheses,
+    /// A comma outside of a function call or matrix.
+    MisplacedComma,
+    /// The input ends in the middle of an expression.
+    UnexpectedEnd,
+    /// An operator or function without enough operands.
+    MissingOperand,
+    /// A definition that repeats a parameter name.
+    DuplicateParameter,
+    /// An equation `a = b` outside of `solve`.
+    MisplacedEquation,
+    /// A variable name was expected, as in `d(f, x)`.
+    ExpectedVariable,
+    /// A semicolon outside of a matrix.
+    MisplacedSemicolon,
+    /// Matrix rows of different lengths.
+    RaggedMatrix,
+}
+
+/// An error from parsing or evaluating an expression, with the span of the input it refers to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CalcError {
+    /// The input contains an invalid character or number.
+    Lex {
+        /// What is wrong with the input.
+        kind: LexErrorKind,
+        /// The part of the input the error refers to.
+        span: Span,
+    },
+    /// The tokens do not form a valid statement.
+    Syntax {
+        /// What is wrong with the statement.
+        kind: SyntaxErrorKind,
+        /// The part of the input the error refers to.
+        span: Span,
+    },
+    /// A name that is not a variable, constant, unit or function.
+    UnknownName {
+        /// The unknown name.
+        name: String,
+        /// The part of the input the error refers to.
+        span: Span,
+    },
+    /// An assignment or definition that would replace a built-in name.
+    ReservedName {
+        /// The reserved name.
+        name: String,
+        /// The part of the input the error refers to.
+        span: Span,
+    },
+    /// A function called with the wrong number of arguments.
+    Arity {
+        /// The function name.
+        name: String,
+        /// The arguments the function takes.
+        expected: Arity,
+        /// The number of arguments given.
+        found: usize,
+        /// The part of the input the error refers to.
+        span: Span,
+    },
+    /// A compiled expression evaluated with the wrong number of inputs.
+    Inputs {
+        /// The number of inputs of the expression.
+        expected: usize,
+        /// The number of values given.
+        found: usize,
+        /// The part of the input the error refers to.
+        span: Span,
+    },
+    /// User functions nested too deeply.
+    RecursionLimit {
+        /// The function that hit the limit.
+        name: String,
+        /// The part of the input the error refers to.
+        span: Span,
+    },
+    /// An argument outside of the domain of an operation, or an operation the backend does not support.
+    Domain {
+        /// What went wrong.
+        message: String,
+        /// The part of the input the error refers to.
+        span: Span,
+    },
+    /// Units that do not match, as in `1 m + 1 s`.
+    Dimension {
+        /// What went wrong.
+        message: String,
+        /// The part of the input the error refers to.
+        span: Span,
+    },
+    /// A numeric method such as `solve` that did not converge.
+    Convergence {
+        /// The method that failed.
+        name: String,
+        /// The part of the input the error refers to.
+        span: Span,
+    },
+    /// A result too large for the backend.
+    Overflow {
+        /// The part of the input the error refers to.
+        span: Span,
+    },
+    /// Division by zero.
+    DivisionByZero {
+        /// The part of the input the error refers to.
+        span: Span,
+    },
+}
+
+impl CalcError {
+    /// Returns the part of the input the error refers to.
+    pub fn span(&self) -> Span {
+        match self {
+            CalcError::Lex { span, .. }
+            | CalcError::Syntax { span, .. }
+            | CalcError::UnknownName { span, .. }
+            | CalcError::ReservedName { span, .. }
+            | CalcError::Arity { span, .. }
+            | CalcError::Inputs { span, .. }
+            | CalcError::RecursionLimit { span, .. }
+            | CalcError::Domain { span, .. }
+            | CalcError::Dimension { span, .. }
+            | CalcError::Convergence { span, .. }
+            | CalcError::Overflow { span }
+            | CalcError::DivisionByZero { span } => *span,
+        }
+    }
+
+    /// Returns true if the input could not be parsed, as opposed to failing during evaluation.
+    pub fn is_parse_error(&self) -> bool {
+        matches!(self, CalcError::Lex { .. } | CalcError::Syntax { .. } | CalcError::ReservedName { .. })
+    }
+
+    pub(crate) fn with_span(mut self, new_span: Span) -> Self {
+        match &mut self {
+            CalcError::Lex { span, .. }
+            | CalcError::Syntax { span, .. }
+            | CalcError::UnknownName { span, .. }
+            | CalcError::ReservedName { span, .. }
+            | CalcError::Arity { span, .. }
+            | CalcError::Inputs { span, .. }
+            | CalcError::RecursionLimit { span, .. }
+            | CalcError::Domain { span, .. }
+            | CalcError::Dimension { span, .. }
+            | CalcError::Convergence { span, .. }
+            | CalcError::Overflow { span }
+            | CalcError::DivisionByZero { span } => *span = new_span,
+        }
+        self
+    }
+
+    /// Returns the error text without its position.
+    pub fn message(&self) -> String {
+        match self {
+            CalcError::Lex { kind: LexErrorKind::UnexpectedCharacter(character), .. } => format!("Unexpected character {}", character),
+            CalcError::Lex { kind: LexErrorKind::InvalidNumber, .. } => String::from("Invalid number"),
+            CalcError::Syntax { kind, .. } => String::from(match kind {
+                SyntaxErrorKind::UnexpectedToken => "Unexpected token",
+                SyntaxErrorKind::UnbalancedParenthesis => "Unbalanced parenthesis",
+                SyntaxErrorKind::EmptyParentheses => "Empty parentheses",
+                SyntaxErrorKind::MisplacedComma => "Comma outside of a function call or matrix",
+                SyntaxErrorKind::UnexpectedEnd => "Unexpected end of expression",
+                SyntaxErrorKind::MissingOperand => "Missing operand",
+                SyntaxErrorKind::DuplicateParameter => "Duplicate parameter",
+                SyntaxErrorKind::MisplacedEquation => "Equation outside of solve",
+                SyntaxErrorKind::ExpectedVariable => "Expected a variable name",
+                SyntaxErrorKind::MisplacedSemicolon => "Semicolon outside of a matrix",
+                SyntaxErrorKind::RaggedMatrix => "Matrix rows have different lengths",
+            }),
+            CalcError::UnknownName { name, .. } => format!("Unknown name {}", name),
+            CalcError::ReservedName { name, .. } => format!("{} is a reserved name", name),
+            CalcError::Arity { name, expected, found, .. } => format!("{} expects {}, got {}", name, expected, found),
+            CalcError::Inputs { expected, found, .. } => format!("Expected {} input values, got {}", expected, found),
+            CalcError::RecursionLimit { name, .. } => format!("Recursion limit reached in {}", name),
+            CalcError::Domain { message, .. } | CalcError::Dimension { message, .. } => message.clone(),
+            CalcError::Convergence { name, .. } => format!("{} did not converge", name),
+            CalcError::Overflow { .. } => String::from("Result is too large"),
+            CalcError::DivisionByZero { .. } => String::from("Cannot divide by zero"),
+        }
+    }
+}
+
+impl fmt::Display for CalcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedEnd, .. } = self {
+            return write!(f, "{}", self.message());
+        }
+        write!(f, "{} at position {}", self.message(), self.span().start + 1)
+    }
+}
+
+impl std::error::Error for CalcError {}
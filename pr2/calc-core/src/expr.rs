@@ -0,0 +1,45 @@
+use super::token::{Operator, Span, UnaryOperator};
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ExprKind {
+    Number(String),
+    Unary {
+        operator: UnaryOperator,
+        operand: Box<Expr>,
+    },
+    Binary {
+        operator: Operator,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    Factorial {
+        operand: Box<Expr>,
+    },
+    Identifier(String),
+    Call {
+        name: String,
+        arguments: Vec<Expr>,
+    },
+    Matrix {
+        rows: Vec<Vec<Expr>>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Expr {
+    pub(crate) kind: ExprKind,
+    pub(crate) span: Span,
+}
+
+impl Expr {
+    pub(crate) fn new(kind: ExprKind, span: Span) -> Self {
+        Expr { kind, span }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Statement {
+    Expression(Expr),
+    Assignment { name: String, value: Expr },
+    Definition { name: String, parameters: Vec<String>, body: Expr },
+}
@@ -0,0 +1,191 @@
+use super::number::{Number, NumberError};
+
+const MAX_PERIODS: f64 = 10_000.0;
+const MAX_ITERATIONS: usize = 200;
+const TOLERANCE: f64 = 1e-12;
+
+fn domain(message: &str) -> NumberError {
+    NumberError::Domain(String::from(message))
+}
+
+fn number<N: Number>(value: f64) -> Result<N, NumberError> {
+    N::from_f64(value).ok_or(NumberError::Overflow)
+}
+
+fn growth<N: Number>(rate: &N, periods: &N) -> Result<N, NumberError> {
+    let base = N::one().checked_add(rate)?;
+    if base.is_negative() || base.is_zero() {
+        return Err(domain("Rate must be greater than -100%"));
+    }
+    base.checked_pow(periods)
+}
+
+fn annuity<N: Number>(rate: &N, periods: &N) -> Result<N, NumberError> {
+    if rate.is_zero() {
+        return Ok(periods.clone());
+    }
+    growth(rate, periods)?.checked_sub(&N::one())?.checked_div(rate)
+}
+
+fn check_periods<N: Number>(periods: &N) -> Result<(), NumberError> {
+    if periods.is_negative() || periods.is_zero() {
+        return Err(domain("Number of periods must be positive"));
+    }
+    Ok(())
+}
+
+pub(crate) fn payment<N: Number>(rate: &N, periods: &N, present: &N, future: &N) -> Result<N, NumberError> {
+    check_periods(periods)?;
+    let owed = present.checked_mul(&growth(rate, periods)?)?.checked_add(future)?;
+    Ok(owed.checked_div(&annuity(rate, periods)?)?.negate())
+}
+
+pub(crate) fn future_value<N: Number>(rate: &N, periods: &N, payment: &N, present: &N) -> Result<N, NumberError> {
+    check_periods(periods)?;
+    let grown = present.checked_mul(&growth(rate, periods)?)?;
+    Ok(grown.checked_add(&payment.checked_mul(&annuity(rate, periods)?)?)?.negate())
+}
+
+pub(crate) fn present_value<N: Number>(rate: &N, periods: &N, payment: &N, future: &N) -> Result<N, NumberError> {
+    check_periods(periods)?;
+    let owed = future.checked_add(&payment.checked_mul(&annuity(rate, periods)?)?)?;
+    Ok(owed.checked_div(&growth(rate, periods)?)?.negate())
+}
+
+pub(crate) fn net_present_value<N: Number>(rate: &N, flows: &[N]) -> Result<N, NumberError> {
+    let factor = N::one().checked_div(&growth(rate, &N::one())?)?;
+    let mut discount = factor.clone();
+    let mut total = N::zero();
+    for flow in flows {
+        total = total.checked_add(&flow.checked_mul(&discount)?)?;
+        discount = discount.checked_mul(&factor)?;
+    }
+    Ok(total)
+}
+
+pub(crate) fn internal_rate<N: Number>(flows: &[N]) -> Result<N, NumberError> {
+    let flows = flows.iter().map(Number::to_f64);
+    if !flows.clone().any(|flow| flow > 0.0) || !flows.clone().any(|flow| flow < 0.0) {
+        return Err(domain("irr needs both positive and negative cash flows"));
+    }
+    let value = |rate: f64| flows.clone().rev().fold(0.0, |total, flow| total / (1.0 + rate) + flow);
+    let slope = |rate: f64| {
+        flows.clone().enumerate().skip(1).map(|(index, flow)| -(index as f64) * flow / (1.0 + rate).powi(index as i32 + 1)).sum::<f64>()
+    };
+    let mut rate = 0.1;
+    for _ in 0..MAX_ITERATIONS {
+        let step = value(rate) / slope(rate);
+        if !step.is_finite() {
+            break;
+        }
+        rate -= step;
+        if rate <= -1.0 {
+            break;
+        }
+        if step.abs() < TOLERANCE * rate.abs().max(1.0) {
+            return number(rate);
+        }
+    }
+    Err(domain("irr did not converge"))
+}
+
+pub(crate) fn simple_interest<N: Number>(principal: &N, rate: &N, time: &N) -> Result<N, NumberError> {
+    principal.checked_mul(rate)?.checked_mul(time)
+}
+
+pub(crate) fn compound_interest<N: Number>(principal: &N, rate: &N, time: &N, per_year: &N) -> Result<N, NumberError> {
+    check_periods(per_year)?;
+    let periods = per_year.checked_mul(time)?;
+    principal.checked_mul(&growth(&rate.checked_div(per_year)?, &periods)?.checked_sub(&N::one())?)
+}
+
+pub(crate) fn amortization<N: Number>(rate: &N, periods: &N, principal: &N) -> Result<Vec<[N; 5]>, NumberError> {
+    check_periods(periods)?;
+    if !periods.is_integer() || periods.to_f64() > MAX_PERIODS {
+        return Err(domain("Number of periods must be a whole number up to 10000"));
+    }
+    let count = periods.to_f64() as usize;
+    let installment = payment(rate, periods, principal, &N::zero())?.negate();
+    let mut balance = principal.clone();
+    let mut schedule = Vec::with_capacity(count);
+    for period in 1..=count {
+        let interest = balance.checked_mul(rate)?;
+        let (installment, repaid) = if period == count {
+            (balance.checked_add(&interest)?, balance.clone())
+        }
+        else {
+            (installment.clone(), installment.checked_sub(&interest)?)
+        };
+        balance = balance.checked_sub(&repaid)?;
+        schedule.push([number(period as f64)?, installment, interest, repaid, balance.clone()]);
+    }
+    Ok(schedule)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Backend, CalcError, Calculator};
+
+    fn value(calculator: &Calculator, input: &str) -> f64 {
+        calculator.evaluate_expression(input).unwrap_or_else(|error| panic!("{} gave {}", input, error)).to_f64()
+    }
+
+    #[test]
+    fn matches_known_answers() {
+        let calculator = Calculator::default();
+        for (input, expected) in [
+            ("pmt(0.05/12, 360, 200000)", -1073.64),
+            ("pmt(0, 10, 1000)", -100.0),
+            ("fv(0.05, 10, -100)", 1257.79),
+            ("pv(0.08/12, 240, 500)", -59777.15),
+            ("npv(0.1, -1000, 300, 400, 500)", -19.12),
+            ("irr(-1000, 300, 400, 500)", 0.0890),
+            ("simple(1000, 0.05, 3)", 150.0),
+            ("compound(1000, 0.05, 10)", 628.89),
+            ("compound(1000, 0.05, 10, 12)", 647.01),
+            ("200 + 15%", 230.0),
+            ("200 - 15%", 170.0),
+        ] {
+            assert!((value(&calculator, input) - expected).abs() < 0.005, "{} gave {}", input, value(&calculator, input));
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_periods_and_rates() {
+        let calculator = Calculator::default();
+        for input in ["pmt(0.05, 0, 100)", "fv(0.05, -3, 100)", "pv(0.05, -3, 100)", "compound(1000, -2, 1)", "amort(0.01, 2.5, 100)", "irr(100, 200)"] {
+            assert!(matches!(calculator.evaluate_expression(input), Err(CalcError::Domain { .. })), "{}", input);
+        }
+    }
+
+    #[test]
+    fn keeps_amortization_tables_out_of_arithmetic() {
+        let mut calculator = Calculator::default();
+        for input in ["amort(0.01, 12, 1000) + 1", "2 * amort(0.01, 12, 1000)", "max(amort(0.01, 12, 1000))", "[amort(0.01, 2, 100)]", "-amort(0.01, 2, 100)"] {
+            match calculator.evaluate_expression(input) {
+                Err(CalcError::Domain { message, .. }) => assert_eq!(message, "amort returns a table and cannot be used as an operand", "{}", input),
+                result => panic!("{} gave {:?}", input, result),
+            }
+        }
+        assert!(calculator.calculate("schedule = amort(0.01, 12, 1000)").is_ok());
+        assert!(matches!(calculator.calculate("f(r) = amort(r, 12, 1000)"), Ok(None)));
+        assert!(matches!(calculator.evaluate_expression("f(0.01)"), Ok(crate::Value::Matrix { rows: 12, columns: 5, .. })));
+    }
+
+    #[test]
+    fn amortizes_to_a_zero_balance() {
+        let mut calculator = Calculator::default();
+        for backend in [Backend::Float, Backend::Decimal, Backend::Rational] {
+            calculator.set_backend(backend);
+            let schedule = calculator.evaluate_expression("amort(0.05/12, 60, 20000)").unwrap();
+            let crate::Value::Matrix { rows, columns, elements } = schedule else { panic!("amort returned {}", schedule) };
+            assert_eq!((rows, columns), (60, 5));
+            let last = &elements[elements.len() - columns..];
+            assert_eq!(last[0].to_f64(), 60.0);
+            assert_eq!(last[4].to_f64(), 0.0, "{:?}", backend);
+            assert!((elements[1].to_f64() - 377.42).abs() < 0.005);
+            let repaid = elements.chunks(columns).map(|row| row[3].to_f64()).sum::<f64>();
+            assert!((repaid - 20000.0).abs() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,364 @@
+use std::f64::consts::{E, PI};
+use std::fmt;
+
+use super::finance;
+use super::number::{Number, NumberError};
+use super::statistics;
+
+const MAX_FACTORIAL: f64 = 10_000.0;
+pub(crate) const IMAGINARY: &str = "i";
+
+/// The angle unit of trigonometric functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AngleMode {
+    /// Angles in radians.
+    #[default]
+    Radians,
+    /// Angles in degrees.
+    Degrees,
+}
+
+impl AngleMode {
+    /// Returns `RAD` or `DEG`.
+    pub fn label(self) -> &'static str {
+        match self {
+            AngleMode::Radians => "RAD",
+            AngleMode::Degrees => "DEG",
+        }
+    }
+
+    /// Returns the other angle mode.
+    pub fn toggled(self) -> AngleMode {
+        match self {
+            AngleMode::Radians => AngleMode::Degrees,
+            AngleMode::Degrees => AngleMode::Radians,
+        }
+    }
+
+    fn angle_to_radians(self, angle: f64) -> f64 {
+        match self {
+            AngleMode::Radians => angle,
+            AngleMode::Degrees => angle.to_radians(),
+        }
+    }
+
+    fn radians_to_angle(self, angle: f64) -> f64 {
+        match self {
+            AngleMode::Radians => angle,
+            AngleMode::Degrees => angle.to_degrees(),
+        }
+    }
+}
+
+/// The number of arguments a function takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    /// Exactly this many.
+    Exact(usize),
+    /// This many or more.
+    AtLeast(usize),
+    /// Between the two bounds, inclusive.
+    Range(usize, usize),
+}
+
+impl Arity {
+    /// Returns true if a call with `count` arguments is allowed.
+    pub fn accepts(self, count: usize) -> bool {
+        match self {
+            Arity::Exact(expected) => count == expected,
+            Arity::AtLeast(minimum) => count >= minimum,
+            Arity::Range(minimum, maximum) => (minimum..=maximum).contains(&count),
+        }
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Arity::Exact(1) => write!(f, "1 argument"),
+            Arity::Exact(count) => write!(f, "{} arguments", count),
+            Arity::AtLeast(1) => write!(f, "at least 1 argument"),
+            Arity::AtLeast(count) => write!(f, "at least {} arguments", count),
+            Arity::Range(minimum, maximum) => write!(f, "{} to {} arguments", minimum, maximum),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Sqrt,
+    Ln,
+    Log10,
+    Log,
+    Abs,
+    Floor,
+    Ceil,
+    Round,
+    Min,
+    Max,
+    Arg,
+    Conj,
+    Re,
+    Im,
+    Transpose,
+    Det,
+    Inv,
+    Linsolve,
+    Mean,
+    Median,
+    Mode,
+    Stdev,
+    Variance,
+    Percentile,
+    Linreg,
+    Corr,
+    Pmt,
+    Fv,
+    Pv,
+    Npv,
+    Irr,
+    Simple,
+    Compound,
+    Amort,
+    Simplify,
+}
+
+const NAMES: [(&str, Function); 41] = [
+    ("sin", Function::Sin),
+    ("cos", Function::Cos),
+    ("tan", Function::Tan),
+    ("asin", Function::Asin),
+    ("acos", Function::Acos),
+    ("atan", Function::Atan),
+    ("sqrt", Function::Sqrt),
+    ("ln", Function::Ln),
+    ("log10", Function::Log10),
+    ("log", Function::Log),
+    ("abs", Function::Abs),
+    ("floor", Function::Floor),
+    ("ceil", Function::Ceil),
+    ("round", Function::Round),
+    ("min", Function::Min),
+    ("max", Function::Max),
+    ("arg", Function::Arg),
+    ("conj", Function::Conj),
+    ("re", Function::Re),
+    ("im", Function::Im),
+    ("transpose", Function::Transpose),
+    ("det", Function::Det),
+    ("inv", Function::Inv),
+    ("linsolve", Function::Linsolve),
+    ("mean", Function::Mean),
+    ("median", Function::Median),
+    ("mode", Function::Mode),
+    ("stdev", Function::Stdev),
+    ("variance", Function::Variance),
+    ("percentile", Function::Percentile),
+    ("linreg", Function::Linreg),
+    ("corr", Function::Corr),
+    ("pmt", Function::Pmt),
+    ("fv", Function::Fv),
+    ("pv", Function::Pv),
+    ("npv", Function::Npv),
+    ("irr", Function::Irr),
+    ("simple", Function::Simple),
+    ("compound", Function::Compound),
+    ("amort", Function::Amort),
+    ("simplify", Function::Simplify),
+];
+
+impl Function {
+    pub(crate) fn from_name(name: &str) -> Option<Function> {
+        NAMES.iter().find(|(candidate, _)| *candidate == name).map(|(_, function)| *function)
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        NAMES.iter().find(|(_, function)| *function == self).map_or("", |(name, _)| name)
+    }
+
+    pub(crate) fn arity(self) -> Arity {
+        match self {
+            Function::Log | Function::Linsolve | Function::Percentile | Function::Linreg | Function::Corr => Arity::Exact(2),
+            Function::Pmt | Function::Fv | Function::Pv | Function::Compound => Arity::Range(3, 4),
+            Function::Simple | Function::Amort => Arity::Exact(3),
+            Function::Npv => Arity::AtLeast(2),
+            Function::Irr => Arity::AtLeast(1),
+            _ if self.is_aggregate() => Arity::AtLeast(1),
+            _ => Arity::Exact(1),
+        }
+    }
+
+    pub(crate) fn accepts_units(self) -> bool {
+        matches!(
+            self,
+            Function::Abs | Function::Floor | Function::Ceil | Function::Round | Function::Sqrt | Function::Min | Function::Max | Function::Transpose
+                | Function::Mean | Function::Median | Function::Mode | Function::Percentile | Function::Linreg | Function::Simplify
+        )
+    }
+
+    pub(crate) fn is_transcendental(self) -> bool {
+        matches!(
+            self,
+            Function::Sin | Function::Cos | Function::Tan | Function::Asin | Function::Acos | Function::Atan | Function::Sqrt
+                | Function::Ln | Function::Log10 | Function::Log | Function::Arg
+        )
+    }
+
+    pub(crate) fn is_aggregate(self) -> bool {
+        matches!(self, Function::Min | Function::Max | Function::Mean | Function::Median | Function::Mode | Function::Stdev | Function::Variance)
+    }
+
+    pub(crate) fn is_financial(self) -> bool {
+        matches!(self, Function::Pmt | Function::Fv | Function::Pv | Function::Simple | Function::Compound | Function::Amort)
+    }
+
+    pub(crate) fn apply<N: Number>(self, arguments: &[N], angle_mode: AngleMode) -> Result<N, NumberError> {
+        if let Some(result) = N::apply_function(self, arguments, angle_mode) {
+            return result;
+        }
+        if let Some(error) = N::inexact(self.name()).filter(|_| self.is_transcendental()) {
+            return Err(error);
+        }
+        let x = &arguments[0];
+        match self {
+            Function::Sin => real(angle_mode.angle_to_radians(x.to_f64()).sin()),
+            Function::Cos => real(angle_mode.angle_to_radians(x.to_f64()).cos()),
+            Function::Tan => {
+                let radians = angle_mode.angle_to_radians(x.to_f64());
+                if radians.cos().abs() < 1e-12 {
+                    return Err(domain("Tangent is undefined"));
+                }
+                real(radians.tan())
+            }
+            Function::Asin | Function::Acos => {
+                let x = x.to_f64();
+                if !(-1.0..=1.0).contains(&x) {
+                    return Err(domain("Argument must be between -1 and 1"));
+                }
+                let radians = if self == Function::Asin { x.asin() } else { x.acos() };
+                real(angle_mode.radians_to_angle(radians))
+            }
+            Function::Atan => real(angle_mode.radians_to_angle(x.to_f64().atan())),
+            Function::Sqrt => {
+                if x.is_negative() {
+                    return Err(domain("Square root of a negative number"));
+                }
+                real(x.to_f64().sqrt())
+            }
+            Function::Ln | Function::Log10 => {
+                if x.is_negative() || x.is_zero() {
+                    return Err(domain("Logarithm of a non-positive number"));
+                }
+                let x = x.to_f64();
+                real(if self == Function::Ln { x.ln() } else { x.log10() })
+            }
+            Function::Log => {
+                let (base, value) = (x.to_f64(), arguments[1].to_f64());
+                if base <= 0.0 || base == 1.0 {
+                    return Err(domain("Logarithm base must be positive and not 1"));
+                }
+                if value <= 0.0 {
+                    return Err(domain("Logarithm of a non-positive number"));
+                }
+                real(value.log(base))
+            }
+            Function::Abs => Ok(x.abs()),
+            Function::Floor => Ok(x.floor()),
+            Function::Ceil => Ok(x.ceil()),
+            Function::Round => Ok(x.round()),
+            Function::Arg => real(angle_mode.radians_to_angle(if x.is_negative() { PI } else { 0.0 })),
+            Function::Conj | Function::Re | Function::Transpose | Function::Det | Function::Simplify => Ok(x.clone()),
+            Function::Inv | Function::Linsolve => {
+                if x.is_zero() {
+                    return Err(domain("Matrix is singular"));
+                }
+                let numerator = if self == Function::Inv { N::one() } else { arguments[1].clone() };
+                numerator.checked_div(x)
+            }
+            Function::Im => Ok(N::zero()),
+            Function::Min | Function::Max => {
+                let mut result = x.clone();
+                for argument in &arguments[1..] {
+                    argument.check_compatible(&result)?;
+                    if (self == Function::Min && *argument < result) || (self == Function::Max && *argument > result) {
+                        result = argument.clone();
+                    }
+                }
+                Ok(result)
+            }
+            Function::Mean => statistics::mean(arguments),
+            Function::Median => statistics::median(arguments),
+            Function::Mode => statistics::mode(arguments),
+            Function::Stdev => Function::Sqrt.apply(&[statistics::variance(arguments)?], angle_mode),
+            Function::Variance => statistics::variance(arguments),
+            Function::Percentile => {
+                let (percent, values) = arguments.split_last().expect("percentile takes two arguments");
+                statistics::percentile(values, percent)
+            }
+            Function::Linreg | Function::Corr => Err(domain("Regression needs at least two points")),
+            Function::Pmt | Function::Fv | Function::Pv => {
+                let (periods, amount) = (&arguments[1], &arguments[2]);
+                let last = arguments.get(3).cloned().unwrap_or_else(N::zero);
+                match self {
+                    Function::Pmt => finance::payment(x, periods, amount, &last),
+                    Function::Fv => finance::future_value(x, periods, amount, &last),
+                    _ => finance::present_value(x, periods, amount, &last),
+                }
+            }
+            Function::Npv => finance::net_present_value(x, &arguments[1..]),
+            Function::Irr => finance::internal_rate(arguments),
+            Function::Simple => finance::simple_interest(x, &arguments[1], &arguments[2]),
+            Function::Compound => {
+                let per_year = arguments.get(3).cloned().unwrap_or_else(N::one);
+                finance::compound_interest(x, &arguments[1], &arguments[2], &per_year)
+            }
+            Function::Amort => Err(domain("amort returns a table and needs matrix evaluation")),
+        }
+    }
+}
+
+fn real<N: Number>(value: f64) -> Result<N, NumberError> {
+    N::from_f64(value).ok_or(NumberError::Overflow)
+}
+
+fn domain(message: &str) -> NumberError {
+    NumberError::Domain(String::from(message))
+}
+
+pub(crate) fn approximation<N: Number>(value: f64, what: &str) -> Result<N, NumberError> {
+    match N::inexact(what) {
+        Some(error) => Err(error),
+        None => real(value),
+    }
+}
+
+pub(crate) fn constant<N: Number>(name: &str) -> Option<Result<N, NumberError>> {
+    match name {
+        "pi" => Some(approximation(PI, name)),
+        "e" => Some(approximation(E, name)),
+        IMAGINARY => N::imaginary_unit().map(Ok),
+        _ => None,
+    }
+}
+
+pub(crate) fn factorial<N: Number>(n: &N) -> Result<N, NumberError> {
+    if n.is_negative() || !n.is_integer() {
+        return Err(domain("Factorial is defined for non-negative integers only"));
+    }
+    if n.to_f64() > MAX_FACTORIAL {
+        return Err(NumberError::Overflow);
+    }
+    let mut result = N::one();
+    let mut factor = N::one();
+    while factor < *n {
+        factor = factor.checked_add(&N::one())?;
+        result = result.checked_mul(&factor)?;
+    }
+    Ok(result)
+}
@@ -0,0 +1,1545 @@
+//! Expression evaluation for the calculator: parsing, f64, decimal, exact rational, complex and
+//! fixed-width integer backends, units, matrices, symbolic simplification and compiled expressions.
+//!
+//! ```
+//! use calc_core::{Backend, Calculator};
+//!
+//! let mut calculator = Calculator::default();
+//! calculator.set_backend(Backend::Rational);
+//! let third = calculator.calculate("x = 1/3").unwrap().unwrap();
+//! assert_eq!(third.to_string(), "1/3");
+//! assert_eq!(calculator.evaluate_expression("3 * x").unwrap().to_string(), "1");
+//! ```
+//!
+//! [`Value`] holds numbers of the `rust_decimal` and `num-rational` crates. They are re-exported
+//! so that callers can use them without depending on matching versions.
+
+#![warn(missing_docs)]
+
+mod calculus;
+mod compiled;
+mod complex;
+mod error;
+mod expr;
+mod finance;
+mod functions;
+mod matrix;
+mod number;
+mod scope;
+mod statistics;
+mod symbolic;
+mod token;
+mod trace;
+mod units;
+mod word;
+
+use self::calculus::{Calculus, DEFAULT_VARIABLE, MAX_TERMS};
+pub use self::compiled::CompiledExpr;
+use self::compiled::{Instruction, Program};
+pub use self::complex::{Complex, ComplexDisplay};
+pub use self::error::{CalcError, LexErrorKind, SyntaxErrorKind};
+use self::expr::{Expr, ExprKind, Statement};
+pub use self::functions::{AngleMode, Arity};
+use self::functions::{Function, IMAGINARY};
+use self::matrix::Matrix;
+pub use self::number::{Backend, DisplayOptions, RationalDisplay, Value};
+use self::number::{complex_only, parse_integer, Number, NumberError};
+pub use self::scope::UserFunction;
+use self::scope::{Scope, MAX_CALL_DEPTH};
+use self::symbolic::Symbolic;
+use num_rational::BigRational;
+use rust_decimal::Decimal;
+pub use self::token::Span;
+use self::token::{BitwiseOperator, Operator, Token, TokenKind, UnaryOperator};
+pub use self::trace::{Trace, TraceStep};
+pub use self::units::QuantityValue;
+use self::units::{Dimension, Quantity, Unit, BASE_UNITS, BUILTIN_UNITS};
+pub use self::word::WordSize;
+use self::word::Word;
+use std::collections::BTreeMap;
+
+pub use num_bigint;
+pub use num_rational;
+pub use rust_decimal;
+
+const ANSWER: &str = "ans";
+const XOR: &str = "xor";
+const CONVERT: &str = "in";
+const SIMPLIFY: &str = "simplify";
+const DIFFERENTIAL: &str = "d";
+const OPERAND: &str = "operand";
+
+/// Evaluates expressions and keeps variables, user functions, units and the current modes.
+pub struct Calculator {
+    angle_mode: AngleMode,
+    backend: Backend,
+    word_size: WordSize,
+    variables: BTreeMap<String, Value>,
+    user_functions: BTreeMap<String, UserFunction>,
+    units: BTreeMap<String, Unit>,
+}
+
+impl Default for Calculator {
+    fn default() -> Self {
+        let units = BASE_UNITS.iter()
+            .enumerate()
+            .map(|(index, name)| (name.to_string(), Unit { factor: BigRational::from_integer(1.into()), dimension: Dimension::base(index) }))
+            .collect();
+        let mut calculator = Calculator {
+            angle_mode: AngleMode::default(),
+            backend: Backend::default(),
+            word_size: WordSize::default(),
+            variables: BTreeMap::new(),
+            user_functions: BTreeMap::new(),
+            units,
+        };
+        calculator.load_units(BUILTIN_UNITS).expect("Built-in units are valid");
+        calculator
+    }
+}
+
+impl Calculator {
+    /// Returns the angle unit used by trigonometric functions.
+    pub fn angle_mode(&self) -> AngleMode {
+        self.angle_mode
+    }
+
+    /// Sets the angle unit used by trigonometric functions.
+    pub fn set_angle_mode(&mut self, angle_mode: AngleMode) {
+        self.angle_mode = angle_mode;
+    }
+
+    /// Returns the number type expressions are evaluated in.
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// Switches the number type for later evaluations. Stored variables are converted when they are used.
+    pub fn set_backend(&mut self, backend: Backend) {
+        self.backend = backend;
+    }
+
+    /// Returns the integer width of the programmer backend.
+    pub fn word_size(&self) -> WordSize {
+        self.word_size
+    }
+
+    /// Sets the integer width of the programmer backend.
+    pub fn set_word_size(&mut self, word_size: WordSize) {
+        self.word_size = word_size;
+    }
+
+    /// Returns the stored variables, including `ans` after a calculation.
+    pub fn variables(&self) -> &BTreeMap<String, Value> {
+        &self.variables
+    }
+
+    /// Removes a variable. Unknown names are ignored.
+    pub fn remove_variable(&mut self, name: &str) {
+        self.variables.remove(name);
+    }
+
+    /// Returns the functions defined with `f(x) = expression`.
+    pub fn user_functions(&self) -> &BTreeMap<String, UserFunction> {
+        &self.user_functions
+    }
+
+    /// Removes a user function. Unknown names are ignored.
+    pub fn remove_user_function(&mut self, name: &str) {
+        self.user_functions.remove(name);
+    }
+
+    /// Defines units from `name = expression` lines, where `#` starts a comment.
+    /// On failure returns the 1-based line number with its error. Units from earlier lines stay defined.
+    pub fn load_units(&mut self, source: &str) -> Result<(), (usize, CalcError)> {
+        let variables = std::mem::take(&mut self.variables);
+        let result = source.lines().enumerate().try_for_each(|(index, line)| {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                return Ok(());
+            }
+            self.define_unit(line).map_err(|error| (index + 1, error))
+        });
+        self.variables = variables;
+        result
+    }
+
+    /// Returns true if `name` is a built-in or loaded unit, even when a variable currently hides it.
+    pub fn defines_unit(&self, name: &str) -> bool {
+        self.units.contains_key(name)
+    }
+
+    fn define_unit(&mut self, line: &str) -> Result<(), CalcError> {
+        let Some((name, definition)) = line.split_once('=') else {
+            return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedEnd, span: Span::new(line.len(), line.len()) });
+        };
+        let name = name.trim();
+        if !is_name(name) || name == XOR || name == CONVERT {
+            return Err(CalcError::ReservedName { name: name.to_string(), span: Span::new(0, name.len()) });
+        }
+        let offset = line.len() - definition.len();
+        let shifted = |error: CalcError| {
+            let span = error.span();
+            error.with_span(span.shifted(offset))
+        };
+        let tree = self.parse(definition).and_then(|tokens| self.tree_from_tokens(tokens)).map_err(shifted)?;
+        let quantity = self.evaluate::<Quantity<BigRational>>(&tree, &Scope::global()).map_err(shifted)?;
+        if quantity.value.is_zero() || quantity.value.is_negative() {
+            let message = String::from("Unit must be positive");
+            return Err(CalcError::Domain { message, span: tree.span.shifted(offset) });
+        }
+        self.units.insert(name.to_string(), Unit { factor: quantity.value, dimension: quantity.dimension });
+        Ok(())
+    }
+
+    /// Evaluates an expression, an assignment `name = expression` or a definition `f(x) = expression`.
+    /// Assigned values are stored, and numeric results are also stored as `ans`. Definitions return `None`.
+    pub fn calculate(&mut self, expression: &str) -> Result<Option<Value>, CalcError> {
+        self.execute(expression, None)
+    }
+
+    /// Works like [`Calculator::calculate`] and records every step of the evaluation on the way.
+    pub fn calculate_traced(&mut self, expression: &str) -> Result<Trace, CalcError> {
+        let mut trace = self.trace_forms(expression)?;
+        trace.result = self.execute(expression, Some(&mut trace.steps))?;
+        Ok(trace)
+    }
+
+    fn execute(&mut self, expression: &str, steps: Option<&mut Vec<TraceStep>>) -> Result<Option<Value>, CalcError> {
+        let value = match self.parse_statement(expression)? {
+            Statement::Expression(tree) => {
+                if let Some(symbolic) = self.symbolic_command(&tree)? {
+                    let result = Value::Expression(symbolic.to_string());
+                    if let Some(steps) = steps {
+                        let operation = expression[tree.span.start..tree.span.end].to_string();
+                        steps.push(TraceStep { operands: Vec::new(), operation, result: result.clone() });
+                    }
+                    return Ok(Some(result));
+                }
+                self.evaluate_statement(&tree, expression, steps)?
+            }
+            Statement::Assignment { name, value } => {
+                let value = self.evaluate_statement(&value, expression, steps)?;
+                self.variables.insert(name, value.clone());
+                value
+            }
+            Statement::Definition { name, parameters, body } => {
+                let source = expression.trim().to_string();
+                self.user_functions.insert(name, UserFunction { parameters, body, source });
+                return Ok(None);
+            }
+        };
+        self.variables.insert(ANSWER.to_string(), value.clone());
+        Ok(Some(value))
+    }
+
+    /// Parses the body of a function of `parameters` for repeated calls with [`Calculator::call_function`].
+    pub fn compile_function(&self, expression: &str, parameters: &[&str]) -> Result<UserFunction, CalcError> {
+        let parameters = parameters.iter().map(|parameter| parameter.to_string()).collect::<Vec<_>>();
+        let body = self.tree_from_tokens(self.parse(expression)?)?;
+        self.check_names(&body, "", &parameters)?;
+        Ok(UserFunction { parameters, body, source: expression.trim().to_string() })
+    }
+
+    /// Calls a function from [`Calculator::compile_function`] in the f64 backend.
+    pub fn call_function(&self, function: &UserFunction, arguments: Vec<f64>) -> Result<f64, CalcError> {
+        self.evaluate(&function.body, &Scope::global().call(function, arguments))
+    }
+
+    /// Compiles a real expression into a stack program. Variables and constants are resolved now,
+    /// other names become inputs of the [`CompiledExpr`]. Units, matrices, calculus and bitwise or polar
+    /// operators are rejected.
+    pub fn compile(&self, expression: &str) -> Result<CompiledExpr, CalcError> {
+        let tree = self.tree_from_tokens(self.parse(expression)?)?;
+        let mut program = Program::default();
+        self.compile_node(&tree, &mut program, &[], 0, 0)?;
+        Ok(program.finish(expression, self.angle_mode))
+    }
+
+    /// Parses a statement without evaluating it.
+    pub fn check(&self, expression: &str) -> Result<(), CalcError> {
+        self.parse_statement(expression).map(|_| ())
+    }
+
+    /// Returns the number of arguments a built-in or user function takes, or `None` for unknown names.
+    pub fn function_arity(&self, name: &str) -> Option<Arity> {
+        if let Some(function) = self.user_functions.get(name) {
+            return Some(Arity::Exact(function.parameters.len()));
+        }
+        Function::from_name(name).map(Function::arity)
+    }
+
+    /// Applies a binary operator such as `+`, `.*` or `xor` in the current backend.
+    pub fn apply_operator(&self, operator: &str, left: &Value, right: &Value) -> Result<Value, CalcError> {
+        let span = Span::new(0, operator.len());
+        let Some(operator) = Operator::from_symbol(operator) else {
+            return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedToken, span });
+        };
+        let [left_operand, right_operand] = [0, 1].map(|index| operand(index, span));
+        let tree = Expr::new(ExprKind::Binary { operator, left: Box::new(left_operand), right: Box::new(right_operand) }, span);
+        self.evaluate_value(&tree, "", &[left.clone(), right.clone()])
+    }
+
+    /// Calls a built-in or user function with evaluated arguments.
+    pub fn apply_function(&self, name: &str, arguments: &[Value]) -> Result<Value, CalcError> {
+        let span = Span::new(0, name.len());
+        let operands = (0..arguments.len()).map(|index| operand(index, span)).collect();
+        let tree = Expr::new(ExprKind::Call { name: name.to_string(), arguments: operands }, span);
+        self.evaluate_value(&tree, "", arguments)
+    }
+
+    /// Negates a value in the current backend.
+    pub fn negate(&self, value: &Value) -> Result<Value, CalcError> {
+        let span = Span::new(0, 1);
+        let tree = Expr::new(ExprKind::Unary { operator: UnaryOperator::Minus, operand: Box::new(operand(0, span)) }, span);
+        self.evaluate_value(&tree, "", std::slice::from_ref(value))
+    }
+
+    /// Evaluates an expression without storing anything.
+    pub fn evaluate_expression(&self, expression: &str) -> Result<Value, CalcError> {
+        let tree = self.tree_from_tokens(self.parse(expression)?)?;
+        self.evaluate_value(&tree, expression, &[])
+    }
+
+    /// Evaluates an expression step by step without storing anything.
+    pub fn trace(&self, expression: &str) -> Result<Trace, CalcError> {
+        let Trace { tokens, postfix, mut steps, .. } = self.trace_forms(expression)?;
+        let result = match self.parse_statement(expression)? {
+            Statement::Expression(tree) => match self.symbolic_command(&tree)? {
+                Some(symbolic) => {
+                    let result = Value::Expression(symbolic.to_string());
+                    let operation = expression[tree.span.start..tree.span.end].to_string();
+                    steps.push(TraceStep { operands: Vec::new(), operation, result: result.clone() });
+                    Some(result)
+                }
+                None => Some(self.trace_node(&tree, expression, &mut steps)?),
+            },
+            Statement::Assignment { value, .. } => Some(self.trace_node(&value, expression, &mut steps)?),
+            Statement::Definition { .. } => None,
+        };
+        Ok(Trace { tokens, postfix, steps, result })
+    }
+
+    fn trace_forms(&self, expression: &str) -> Result<Trace, CalcError> {
+        let tokens = self.parse(expression)?;
+        let listed = tokens.iter().filter_map(|token| trace::token_text(token, expression)).collect();
+        let start = tokens.iter().position(|token| token.kind == TokenKind::Assign).map_or(0, |position| position + 1);
+        let postfix = self.postfix_gen(tokens[start..].to_vec()).iter().map(trace::postfix_text).collect();
+        Ok(Trace { tokens: listed, postfix, steps: Vec::new(), result: None })
+    }
+
+    fn evaluate_statement(&self, tree: &Expr, expression: &str, steps: Option<&mut Vec<TraceStep>>) -> Result<Value, CalcError> {
+        match steps {
+            Some(steps) => self.trace_node(tree, expression, steps),
+            None => self.evaluate_value(tree, expression, &[]),
+        }
+    }
+
+    /// Stores a variable. Invalid names and names of constants or functions give [`CalcError::ReservedName`].
+    pub fn set_variable(&mut self, name: &str, value: Value) -> Result<(), CalcError> {
+        if !is_name(name) || self.is_reserved(name) {
+            return Err(CalcError::ReservedName { name: name.to_string(), span: Span::new(0, name.len()) });
+        }
+        self.variables.insert(name.to_string(), value);
+        Ok(())
+    }
+
+    fn symbolic_command(&self, tree: &Expr) -> Result<Option<Symbolic>, CalcError> {
+        match &tree.kind {
+            ExprKind::Call { name, arguments } if name == SIMPLIFY && arguments.len() == 1 => {
+                Ok(Some(symbolic::simplify(&self.symbolic(&arguments[0], 0)?)))
+            }
+            ExprKind::Binary { operator: Operator::Div, left, right } => {
+                let (ExprKind::Identifier(differential), ExprKind::Call { name, arguments }) = (&left.kind, &right.kind) else {
+                    return Ok(None);
+                };
+                let variable = name.strip_prefix(DIFFERENTIAL).unwrap_or_default();
+                if differential != DIFFERENTIAL
+                    || variable.is_empty()
+                    || arguments.len() != 1
+                    || self.variables.contains_key(differential)
+                    || self.user_functions.contains_key(name) {
+                    return Ok(None);
+                }
+                let function = symbolic::simplify(&self.symbolic(&arguments[0], 0)?);
+                let derivative = symbolic::derivative(&function, variable, self.angle_mode)
+                    .map_err(|message| CalcError::Domain { message, span: arguments[0].span })?;
+                Ok(Some(symbolic::simplify(&derivative)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn symbolic(&self, expr: &Expr, depth: usize) -> Result<Symbolic, CalcError> {
+        let unsupported = |what: &str| CalcError::Domain { message: format!("{} cannot be handled symbolically", what), span: expr.span };
+        match &expr.kind {
+            ExprKind::Number(literal) => <BigRational as Number>::parse_literal(literal)
+                .map(Symbolic::Number)
+                .ok_or(CalcError::Lex { kind: LexErrorKind::InvalidNumber, span: expr.span }),
+            ExprKind::Identifier(name) => Ok(Symbolic::symbol(name)),
+            ExprKind::Unary { operator: UnaryOperator::Minus, operand } => Ok(-self.symbolic(operand, depth)?),
+            ExprKind::Unary { operator: UnaryOperator::Plus, operand } => self.symbolic(operand, depth),
+            ExprKind::Unary { operator: UnaryOperator::Not, .. } => Err(unsupported("~")),
+            ExprKind::Unary { operator: UnaryOperator::Degree, operand } => {
+                let angle = self.symbolic(operand, depth)?;
+                match self.angle_mode {
+                    AngleMode::Degrees => Ok(angle),
+                    AngleMode::Radians => Ok(angle * Symbolic::symbol("pi") / Symbolic::Number(BigRational::from_integer(180.into()))),
+                }
+            }
+            ExprKind::Unary { operator: UnaryOperator::Percent, operand } => {
+                Ok(self.symbolic(operand, depth)? / Symbolic::Number(BigRational::from_integer(100.into())))
+            }
+            ExprKind::Factorial { .. } => Err(unsupported("!")),
+            ExprKind::Binary { operator, left, right } => {
+                let (left, right) = (self.symbolic(left, depth)?, self.symbolic(right, depth)?);
+                match operator {
+                    Operator::Add => Ok(left + right),
+                    Operator::Sub => Ok(left - right),
+                    Operator::Mul | Operator::ElementMul => Ok(left * right),
+                    Operator::Div | Operator::ElementDiv => Ok(left / right),
+                    Operator::Pow => Ok(left.pow(right)),
+                    Operator::Bitwise(_) => Err(unsupported("Bitwise operators")),
+                    Operator::Convert => Err(unsupported("Unit conversion")),
+                    Operator::Polar => Err(unsupported("Polar form")),
+                    Operator::Equation => Err(CalcError::Syntax { kind: SyntaxErrorKind::MisplacedEquation, span: expr.span }),
+                }
+            }
+            ExprKind::Call { name, arguments } => {
+                let values = arguments.iter()
+                    .map(|argument| self.symbolic(argument, depth))
+                    .collect::<Result<Vec<Symbolic>, CalcError>>()?;
+                if let Some(function) = Function::from_name(name) {
+                    self.check_arity(name, function.arity(), arguments.len(), expr.span)?;
+                    return Ok(if function == Function::Simplify { symbolic::simplify(&values[0]) } else { Symbolic::call(name, values) });
+                }
+                if Calculus::from_name(name).is_some() {
+                    return Err(unsupported(name));
+                }
+                let function = self.user_functions.get(name)
+                    .ok_or_else(|| CalcError::UnknownName { name: name.clone(), span: expr.span })?;
+                self.check_arity(name, Arity::Exact(function.parameters.len()), arguments.len(), expr.span)?;
+                if depth >= MAX_CALL_DEPTH {
+                    return Err(CalcError::RecursionLimit { name: name.clone(), span: expr.span });
+                }
+                let body = self.symbolic(&function.body, depth + 1).map_err(|error| error.with_span(expr.span))?;
+                let bindings = function.parameters.iter().cloned().zip(values).collect::<Vec<_>>();
+                Ok(body.substitute(&bindings))
+            }
+            ExprKind::Matrix { .. } => Err(unsupported("Matrices")),
+        }
+    }
+
+    fn evaluate_value(&self, tree: &Expr, expression: &str, operands: &[Value]) -> Result<Value, CalcError> {
+        match self.backend {
+            Backend::Float => self.evaluate_quantity::<f64>(tree, expression, operands, Value::Float),
+            Backend::Decimal => self.evaluate_quantity::<Decimal>(tree, expression, operands, Value::Decimal),
+            Backend::Rational => self.evaluate_quantity::<BigRational>(tree, expression, operands, Value::Rational),
+            Backend::Complex => self.evaluate_quantity::<Complex>(tree, expression, operands, Value::Complex),
+            Backend::Programmer => match (self.word_size.bits, self.word_size.signed) {
+                (8, true) => self.evaluate_word::<8, true>(tree, operands),
+                (8, false) => self.evaluate_word::<8, false>(tree, operands),
+                (16, true) => self.evaluate_word::<16, true>(tree, operands),
+                (16, false) => self.evaluate_word::<16, false>(tree, operands),
+                (32, true) => self.evaluate_word::<32, true>(tree, operands),
+                (32, false) => self.evaluate_word::<32, false>(tree, operands),
+                (_, true) => self.evaluate_word::<64, true>(tree, operands),
+                (_, false) => self.evaluate_word::<64, false>(tree, operands),
+            },
+        }
+    }
+
+    fn trace_node(&self, tree: &Expr, expression: &str, steps: &mut Vec<TraceStep>) -> Result<Value, CalcError> {
+        let mut operands = Vec::new();
+        let mut bind = |child: &Expr, steps: &mut Vec<TraceStep>| -> Result<Box<Expr>, CalcError> {
+            operands.push(self.trace_node(child, expression, steps)?);
+            Ok(Box::new(operand(operands.len() - 1, child.span)))
+        };
+        let (kind, operation) = match &tree.kind {
+            ExprKind::Number(_) => return self.evaluate_value(tree, expression, &[]),
+            ExprKind::Unary { operator: UnaryOperator::Plus, operand } => return self.trace_node(operand, expression, steps),
+            ExprKind::Identifier(name) => (tree.kind.clone(), name.clone()),
+            ExprKind::Unary { operator, operand } => {
+                (ExprKind::Unary { operator: *operator, operand: bind(operand, steps)? }, operator.symbol().to_string())
+            }
+            ExprKind::Binary { operator: Operator::Convert, left, right } => {
+                let unit = &expression[right.span.start..right.span.end];
+                let kind = ExprKind::Binary { operator: Operator::Convert, left: bind(left, steps)?, right: right.clone() };
+                (kind, format!("{} {}", CONVERT, unit))
+            }
+            ExprKind::Binary { operator, left, right } => {
+                let left = bind(left, steps)?;
+                let right = bind(right, steps)?;
+                (ExprKind::Binary { operator: *operator, left, right }, operator.symbol().to_string())
+            }
+            ExprKind::Factorial { operand } => (ExprKind::Factorial { operand: bind(operand, steps)? }, String::from("!")),
+            ExprKind::Call { name, .. } if Calculus::from_name(name).is_some() => {
+                (tree.kind.clone(), expression[tree.span.start..tree.span.end].to_string())
+            }
+            ExprKind::Call { name, arguments } => {
+                let arguments = arguments.iter()
+                    .map(|argument| bind(argument, steps).map(|argument| *argument))
+                    .collect::<Result<Vec<Expr>, CalcError>>()?;
+                (ExprKind::Call { name: name.clone(), arguments }, name.clone())
+            }
+            ExprKind::Matrix { rows } => {
+                let operation = format!("[{}×{}]", rows.len(), rows[0].len());
+                let rows = rows.iter()
+                    .map(|row| row.iter().map(|element| bind(element, steps).map(|element| *element)).collect())
+                    .collect::<Result<Vec<Vec<Expr>>, CalcError>>()?;
+                (ExprKind::Matrix { rows }, operation)
+            }
+        };
+        let result = self.evaluate_value(&Expr::new(kind, tree.span), expression, &operands)?;
+        steps.push(TraceStep { operands, operation, result: result.clone() });
+        Ok(result)
+    }
+
+    fn evaluate_bound<N: Number>(&self, tree: &Expr, operands: &[Value]) -> Result<N, CalcError> {
+        let parameters = operand_names(operands.len());
+        let arguments = operands.iter()
+            .map(|operand| value_as::<N>(operand, "Complex value", tree.span))
+            .collect::<Result<Vec<N>, CalcError>>()?;
+        let global = Scope::global();
+        self.evaluate(tree, &global.bind(&parameters, arguments))
+    }
+
+    fn evaluate_quantity<N: Number>(&self, tree: &Expr, expression: &str, operands: &[Value], value: fn(N) -> Value) -> Result<Value, CalcError> {
+        let matrix = self.evaluate_bound::<Matrix<Quantity<N>>>(tree, operands)?;
+        let conversion = match &tree.kind {
+            ExprKind::Binary { operator: Operator::Convert, right, .. } => {
+                let unit = self.evaluate::<Quantity<N>>(right, &Scope::global())?;
+                Some((unit, expression[right.span.start..right.span.end].to_string()))
+            }
+            _ => None,
+        };
+        matrix.into_value(|quantity| {
+            if let Some((unit, name)) = &conversion {
+                let magnitude = quantity.value.checked_div(&unit.value).map_err(|error| error.at(tree.span))?;
+                return Ok(Value::Quantity(Box::new(QuantityValue {
+                    magnitude: value(magnitude),
+                    base: value(quantity.value),
+                    unit: name.clone(),
+                    dimension: quantity.dimension,
+                })));
+            }
+            if quantity.dimension.is_none() {
+                return Ok(value(quantity.value));
+            }
+            Ok(Value::Quantity(Box::new(QuantityValue {
+                magnitude: value(quantity.value.clone()),
+                base: value(quantity.value),
+                unit: quantity.dimension.to_string(),
+                dimension: quantity.dimension,
+            })))
+        })
+    }
+
+    fn evaluate_word<const BITS: u32, const SIGNED: bool>(&self, tree: &Expr, operands: &[Value]) -> Result<Value, CalcError> {
+        let matrix = self.evaluate_bound::<Matrix<Word<BITS, SIGNED>>>(tree, operands)?;
+        matrix.into_value(|word| Ok(Value::Integer { value: word.0, size: WordSize { bits: BITS, signed: SIGNED } }))
+    }
+
+    pub(crate) fn parse_statement(&self, expression: &str) -> Result<Statement, CalcError> {
+        let mut tokens = self.parse(expression)?;
+        let Some(position) = tokens.iter().position(|token| token.kind == TokenKind::Assign) else {
+            return self.tree_from_tokens(tokens).map(Statement::Expression);
+        };
+        let misplaced = CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedToken, span: tokens[position].span };
+        let name = match &tokens[0].kind {
+            TokenKind::Identifier(name) if position == 1 => name.clone(),
+            TokenKind::Function { name, .. } => name.clone(),
+            _ => return Err(misplaced),
+        };
+        if self.is_reserved(&name) {
+            return Err(CalcError::ReservedName { name, span: tokens[0].span });
+        }
+        let body = self.tree_from_tokens(tokens.split_off(position + 1))?;
+        if position == 1 {
+            return Ok(Statement::Assignment { name, value: body });
+        }
+        let [left, inner @ .., right] = &tokens[1..position] else {
+            return Err(misplaced);
+        };
+        if left.kind != TokenKind::LeftParenthesis || right.kind != TokenKind::RightParenthesis {
+            return Err(misplaced);
+        }
+        let parameters = self.parameter_list(inner)?;
+        self.check_names(&body, &name, &parameters)?;
+        Ok(Statement::Definition { name, parameters, body })
+    }
+
+    fn is_reserved(&self, name: &str) -> bool {
+        name == ANSWER || name == XOR || name == CONVERT
+            || functions::constant::<f64>(name).is_some()
+            || Function::from_name(name).is_some()
+            || Calculus::from_name(name).is_some()
+    }
+
+    fn parameter_list(&self, tokens: &[Token]) -> Result<Vec<String>, CalcError> {
+        let mut parameters: Vec<String> = Vec::new();
+        for (index, token) in tokens.iter().enumerate() {
+            match &token.kind {
+                TokenKind::Identifier(name) if index % 2 == 0 => {
+                    if parameters.contains(name) {
+                        return Err(CalcError::Syntax { kind: SyntaxErrorKind::DuplicateParameter, span: token.span });
+                    }
+                    parameters.push(name.clone());
+                }
+                TokenKind::Comma if index % 2 == 1 => {}
+                _ => return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedToken, span: token.span }),
+            }
+        }
+        Ok(parameters)
+    }
+
+    fn check_names(&self, expr: &Expr, function: &str, parameters: &[String]) -> Result<(), CalcError> {
+        match &expr.kind {
+            ExprKind::Number(_) => Ok(()),
+            ExprKind::Unary { operand, .. } | ExprKind::Factorial { operand } => {
+                self.check_names(operand, function, parameters)
+            }
+            ExprKind::Binary { left, right, .. } => {
+                self.check_names(left, function, parameters)?;
+                self.check_names(right, function, parameters)
+            }
+            ExprKind::Identifier(name) => {
+                if parameters.contains(name)
+                    || name == IMAGINARY
+                    || self.variables.contains_key(name)
+                    || functions::constant::<f64>(name).is_some()
+                    || self.units.contains_key(name) {
+                    Ok(())
+                }
+                else {
+                    Err(CalcError::UnknownName { name: name.clone(), span: expr.span })
+                }
+            }
+            ExprKind::Call { name, arguments } => {
+                if let Some(calculus) = Calculus::from_name(name) {
+                    self.check_arity(name, calculus.arity(), arguments.len(), expr.span)?;
+                    let variable_index = calculus.variable_index(arguments.len());
+                    let variable = match variable_index.map(|index| &arguments[index]) {
+                        Some(variable) => self.bound_variable(variable)?,
+                        None => DEFAULT_VARIABLE,
+                    };
+                    let mut parameters = parameters.to_vec();
+                    parameters.push(variable.to_string());
+                    return arguments.iter().enumerate()
+                        .filter(|(index, _)| Some(*index) != variable_index)
+                        .try_for_each(|(_, argument)| self.check_names(argument, function, &parameters));
+                }
+                if name != function && Function::from_name(name).is_none() && !self.user_functions.contains_key(name) {
+                    return Err(CalcError::UnknownName { name: name.clone(), span: expr.span });
+                }
+                arguments.iter().try_for_each(|argument| self.check_names(argument, function, parameters))
+            }
+            ExprKind::Matrix { rows } => {
+                rows.iter().flatten().try_for_each(|element| self.check_names(element, function, parameters))
+            }
+        }
+    }
+
+    fn tree_from_tokens(&self, tokens: Vec<Token>) -> Result<Expr, CalcError> {
+        if let Some(assign) = tokens.iter().find(|token| token.kind == TokenKind::Assign) {
+            return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedToken, span: assign.span });
+        }
+        let postfix_tokens = self.postfix_gen(tokens);
+        let tree = self.tree_from_postfix(postfix_tokens)?;
+        check_tables(&tree, true)?;
+        Ok(tree)
+    }
+
+    pub(crate) fn parse(&self, expression: &str) -> Result<Vec<Token>, CalcError> {
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut open_parentheses: Vec<(usize, Option<usize>)> = Vec::new();
+        let mut expect_operand = true;
+        let mut chars = expression.char_indices().peekable();
+        while let Some((position, char)) = chars.next() {
+            if char == '0' && matches!(chars.peek(), Some(&(_, 'x' | 'X' | 'o' | 'O' | 'b' | 'B'))) {
+                let mut end = position + 1;
+                while let Some(&(next_position, next)) = chars.peek() {
+                    if !next.is_ascii_alphanumeric() {
+                        break;
+                    }
+                    end = next_position + next.len_utf8();
+                    chars.next();
+                }
+                let span = Span::new(position, end);
+                if !expect_operand {
+                    return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedToken, span });
+                }
+                let literal = &expression[position..end];
+                if parse_integer(literal).is_none() {
+                    return Err(CalcError::Lex { kind: LexErrorKind::InvalidNumber, span });
+                }
+                tokens.push(Token::new(TokenKind::Number(literal.to_string()), span));
+                expect_operand = false;
+            }
+            else if char == '.' && matches!(chars.peek(), Some(&(_, '*' | '/'))) {
+                let span = Span::new(position, position + 2);
+                if expect_operand {
+                    return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedToken, span });
+                }
+                let operator = if chars.next().is_some_and(|(_, next)| next == '*') { Operator::ElementMul } else { Operator::ElementDiv };
+                tokens.push(Token::new(TokenKind::Operator(operator), span));
+                expect_operand = true;
+            }
+            else if char.is_ascii_digit() || char == '.' {
+                let mut end = position + char.len_utf8();
+                while let Some(&(next_position, next)) = chars.peek() {
+                    if !next.is_ascii_digit() && next != '.' || self.is_element_operator(&expression[next_position..]) {
+                        break;
+                    }
+                    end = next_position + next.len_utf8();
+                    chars.next();
+                }
+                let mantissa = &expression[position..end];
+                let exponent_length = self.exponent_length(&expression[end..]);
+                for _ in 0..exponent_length {
+                    chars.next();
+                }
+                end += exponent_length;
+                let span = Span::new(position, end);
+                if !expect_operand {
+                    return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedToken, span });
+                }
+                let literal = &expression[position..end];
+                if mantissa.starts_with('.') || mantissa.ends_with('.') || mantissa.matches('.').count() > 1 {
+                    return Err(CalcError::Lex { kind: LexErrorKind::InvalidNumber, span });
+                }
+                tokens.push(Token::new(TokenKind::Number(literal.to_string()), span));
+                expect_operand = false;
+            }
+            else if char.is_alphabetic() {
+                let mut end = position + char.len_utf8();
+                while let Some(&(next_position, next)) = chars.peek() {
+                    if !next.is_alphanumeric() && next != '_' {
+                        break;
+                    }
+                    end = next_position + next.len_utf8();
+                    chars.next();
+                }
+                let span = Span::new(position, end);
+                let name = expression[position..end].to_string();
+                if name == XOR && !expect_operand {
+                    tokens.push(Token::new(TokenKind::Operator(Operator::Bitwise(BitwiseOperator::Xor)), span));
+                    expect_operand = true;
+                    continue;
+                }
+                if name == CONVERT && !expect_operand {
+                    tokens.push(Token::new(TokenKind::Operator(Operator::Convert), span));
+                    expect_operand = true;
+                    continue;
+                }
+                let is_call = expression[end..].trim_start().starts_with('(');
+                if !expect_operand && !is_call && (self.is_unit(&name) || self.is_imaginary(&name)) {
+                    tokens.push(Token::new(TokenKind::Operator(Operator::Mul), Span::new(position, position)));
+                    expect_operand = true;
+                }
+                if !expect_operand {
+                    return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedToken, span });
+                }
+                if is_call {
+                    tokens.push(Token::new(TokenKind::Function { name, arguments: 0 }, span));
+                }
+                else {
+                    tokens.push(Token::new(TokenKind::Identifier(name), span));
+                    expect_operand = false;
+                }
+            }
+            else if char == '!' || char == '°' || char == '%' {
+                let span = Span::new(position, position + char.len_utf8());
+                if expect_operand {
+                    return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedToken, span });
+                }
+                let kind = match char {
+                    '!' => TokenKind::Factorial,
+                    '°' => TokenKind::Degree,
+                    _ => TokenKind::Percent,
+                };
+                tokens.push(Token::new(kind, span));
+            }
+            else if char == ',' || char == ';' {
+                let span = Span::new(position, position + 1);
+                let function_index = open_parentheses.last().and_then(|&(_, index)| index);
+                let in_matrix = function_index.is_some_and(|index| matches!(tokens[index].kind, TokenKind::Matrix { .. }));
+                let Some(function_index) = function_index.filter(|_| char == ',' || in_matrix) else {
+                    let kind = if char == ',' { SyntaxErrorKind::MisplacedComma } else { SyntaxErrorKind::MisplacedSemicolon };
+                    return Err(CalcError::Syntax { kind, span });
+                };
+                if expect_operand {
+                    return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedToken, span });
+                }
+                match &mut tokens[function_index].kind {
+                    TokenKind::Function { arguments, .. } => *arguments += 1,
+                    TokenKind::Matrix { rows } if char == ';' => rows.push(1),
+                    TokenKind::Matrix { rows } => *rows.last_mut().expect("Matrix has a row") += 1,
+                    _ => {}
+                }
+                tokens.push(Token::new(TokenKind::Comma, span));
+                expect_operand = true;
+            }
+            else if let Some(operator) = Operator::from_char(char) {
+                let span = Span::new(position, position + char.len_utf8());
+                if !expect_operand {
+                    tokens.push(Token::new(TokenKind::Operator(operator), span));
+                    expect_operand = true;
+                }
+                else if operator == Operator::Add {
+                    tokens.push(Token::new(TokenKind::UnaryOperator(UnaryOperator::Plus), span));
+                }
+                else if operator == Operator::Sub {
+                    tokens.push(Token::new(TokenKind::UnaryOperator(UnaryOperator::Minus), span));
+                }
+                else {
+                    return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedToken, span });
+                }
+            }
+            else if char == '<' || char == '>' {
+                if chars.next_if(|&(_, next)| next == char).is_none() {
+                    let span = Span::new(position, position + 1);
+                    return Err(CalcError::Lex { kind: LexErrorKind::UnexpectedCharacter(char), span });
+                }
+                let span = Span::new(position, position + 2);
+                if expect_operand {
+                    return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedToken, span });
+                }
+                let operator = if char == '<' { BitwiseOperator::ShiftLeft } else { BitwiseOperator::ShiftRight };
+                tokens.push(Token::new(TokenKind::Operator(Operator::Bitwise(operator)), span));
+                expect_operand = true;
+            }
+            else if char == '~' {
+                let span = Span::new(position, position + 1);
+                if !expect_operand {
+                    return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedToken, span });
+                }
+                tokens.push(Token::new(TokenKind::UnaryOperator(UnaryOperator::Not), span));
+            }
+            else if char == '[' {
+                let span = Span::new(position, position + 1);
+                if !expect_operand {
+                    return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedToken, span });
+                }
+                open_parentheses.push((position, Some(tokens.len())));
+                tokens.push(Token::new(TokenKind::Matrix { rows: vec![1] }, span));
+                tokens.push(Token::new(TokenKind::LeftParenthesis, span));
+            }
+            else if char == '(' {
+                let span = Span::new(position, position + 1);
+                if !expect_operand {
+                    return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedToken, span });
+                }
+                let function_index = match tokens.last() {
+                    Some(Token { kind: TokenKind::Function { .. }, .. }) => Some(tokens.len() - 1),
+                    _ => None,
+                };
+                if let Some(index) = function_index {
+                    if let TokenKind::Function { arguments, .. } = &mut tokens[index].kind {
+                        *arguments = 1;
+                    }
+                }
+                open_parentheses.push((position, function_index));
+                tokens.push(Token::new(TokenKind::LeftParenthesis, span));
+            }
+            else if char == ')' || char == ']' {
+                let span = Span::new(position, position + 1);
+                let Some((_, function_index)) = open_parentheses.pop() else {
+                    return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnbalancedParenthesis, span });
+                };
+                let is_matrix = function_index.is_some_and(|index| matches!(tokens[index].kind, TokenKind::Matrix { .. }));
+                if is_matrix != (char == ']') {
+                    return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnbalancedParenthesis, span });
+                }
+                let is_empty = tokens.last().is_some_and(|last| last.kind == TokenKind::LeftParenthesis);
+                if is_matrix && is_empty {
+                    return Err(CalcError::Syntax { kind: SyntaxErrorKind::EmptyParentheses, span });
+                }
+                if let Some(index) = function_index {
+                    let function = &mut tokens[index];
+                    function.span.end = span.end;
+                    if let TokenKind::Function { arguments, .. } = &mut function.kind {
+                        if is_empty {
+                            *arguments = 0;
+                        }
+                    }
+                }
+                else if is_empty {
+                    return Err(CalcError::Syntax { kind: SyntaxErrorKind::EmptyParentheses, span });
+                }
+                if expect_operand && !(is_empty && function_index.is_some()) {
+                    return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedToken, span });
+                }
+                tokens.push(Token::new(TokenKind::RightParenthesis, span));
+                expect_operand = false;
+            }
+            else if char == '=' {
+                let span = Span::new(position, position + 1);
+                if expect_operand {
+                    return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedToken, span });
+                }
+                let kind = if open_parentheses.is_empty() { TokenKind::Assign } else { TokenKind::Operator(Operator::Equation) };
+                tokens.push(Token::new(kind, span));
+                expect_operand = true;
+            }
+            else if char.is_whitespace() {
+                continue;
+            }
+            else {
+                let span = Span::new(position, position + char.len_utf8());
+                return Err(CalcError::Lex { kind: LexErrorKind::UnexpectedCharacter(char), span });
+            }
+        }
+        if let Some(&(position, _)) = open_parentheses.first() {
+            let span = Span::new(position, position + 1);
+            return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnbalancedParenthesis, span });
+        }
+        if expect_operand {
+            let span = Span::new(expression.len(), expression.len());
+            return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedEnd, span });
+        }
+        Ok(tokens)
+    }
+
+    fn is_unit(&self, name: &str) -> bool {
+        self.units.contains_key(name) && !self.variables.contains_key(name)
+    }
+
+    fn is_element_operator(&self, rest: &str) -> bool {
+        rest.starts_with(".*") || rest.starts_with("./")
+    }
+
+    fn is_imaginary(&self, name: &str) -> bool {
+        name == IMAGINARY && !self.variables.contains_key(name)
+    }
+
+    fn exponent_length(&self, rest: &str) -> usize {
+        let bytes = rest.as_bytes();
+        if !matches!(bytes.first(), Some(b'e' | b'E')) {
+            return 0;
+        }
+        let sign_length = usize::from(matches!(bytes.get(1), Some(b'+' | b'-')));
+        let digits = bytes[1 + sign_length..].iter().take_while(|byte| byte.is_ascii_digit()).count();
+        if digits == 0 { 0 } else { 1 + sign_length + digits }
+    }
+
+    fn postfix_gen(&self, tokens: Vec<Token>) -> Vec<Token> {
+        let mut stack: Vec<Token> = Vec::new();
+        let mut postfix_tokens: Vec<Token> = Vec::new();
+        for token in tokens {
+            match token.kind {
+                TokenKind::Number(_) | TokenKind::Identifier(_) | TokenKind::Factorial | TokenKind::Degree | TokenKind::Percent | TokenKind::Assign => {
+                    postfix_tokens.push(token);
+                }
+                TokenKind::LeftParenthesis | TokenKind::UnaryOperator(_) | TokenKind::Function { .. } | TokenKind::Matrix { .. } => {
+                    stack.push(token);
+                }
+                TokenKind::Comma => {
+                    while stack.last().is_some_and(|top| top.kind != TokenKind::LeftParenthesis) {
+                        postfix_tokens.extend(stack.pop());
+                    }
+                }
+                TokenKind::RightParenthesis => {
+                    while let Some(top) = stack.pop() {
+                        if top.kind == TokenKind::LeftParenthesis {
+                            break;
+                        }
+                        postfix_tokens.push(top);
+                    }
+                    if stack.last().is_some_and(|top| matches!(top.kind, TokenKind::Function { .. } | TokenKind::Matrix { .. })) {
+                        postfix_tokens.extend(stack.pop());
+                    }
+                }
+                TokenKind::Operator(operator) => {
+                    while let Some(top) = stack.last() {
+                        let top_priority = match top.kind {
+                            TokenKind::Operator(top_operator) => top_operator.priority(),
+                            TokenKind::UnaryOperator(top_operator) => top_operator.priority(),
+                            _ => break,
+                        };
+                        if top_priority > operator.priority()
+                            || (top_priority == operator.priority() && !operator.is_right_associative()) {
+                            postfix_tokens.extend(stack.pop());
+                        }
+                        else { break; }
+                    }
+                    stack.push(token);
+                }
+            }
+        }
+        while let Some(top) = stack.pop() {
+            postfix_tokens.push(top);
+        }
+        postfix_tokens
+    }
+
+    fn tree_from_postfix(&self, tokens: Vec<Token>) -> Result<Expr, CalcError> {
+        let mut operands: Vec<Expr> = Vec::new();
+        for token in tokens {
+            let node = match token.kind {
+                TokenKind::Number(value) => Expr::new(ExprKind::Number(value), token.span),
+                TokenKind::UnaryOperator(operator) => {
+                    let operand = operands.pop()
+                        .ok_or(CalcError::Syntax { kind: SyntaxErrorKind::MissingOperand, span: token.span })?;
+                    let span = token.span.to(operand.span);
+                    Expr::new(ExprKind::Unary { operator, operand: Box::new(operand) }, span)
+                }
+                TokenKind::Operator(operator) => {
+                    let missing_operand = CalcError::Syntax { kind: SyntaxErrorKind::MissingOperand, span: token.span };
+                    let mut right = operands.pop().ok_or(missing_operand.clone())?;
+                    let left = operands.pop().ok_or(missing_operand)?;
+                    let span = left.span.to(right.span);
+                    if matches!(operator, Operator::Add | Operator::Sub)
+                        && matches!(right.kind, ExprKind::Unary { operator: UnaryOperator::Percent, .. }) {
+                        let share = ExprKind::Binary { operator: Operator::Mul, left: Box::new(left.clone()), right: Box::new(right) };
+                        right = Expr::new(share, span);
+                    }
+                    Expr::new(ExprKind::Binary { operator, left: Box::new(left), right: Box::new(right) }, span)
+                }
+                TokenKind::Factorial => {
+                    let operand = operands.pop()
+                        .ok_or(CalcError::Syntax { kind: SyntaxErrorKind::MissingOperand, span: token.span })?;
+                    let span = operand.span.to(token.span);
+                    Expr::new(ExprKind::Factorial { operand: Box::new(operand) }, span)
+                }
+                TokenKind::Degree | TokenKind::Percent => {
+                    let operand = operands.pop()
+                        .ok_or(CalcError::Syntax { kind: SyntaxErrorKind::MissingOperand, span: token.span })?;
+                    let span = operand.span.to(token.span);
+                    let operator = if token.kind == TokenKind::Degree { UnaryOperator::Degree } else { UnaryOperator::Percent };
+                    Expr::new(ExprKind::Unary { operator, operand: Box::new(operand) }, span)
+                }
+                TokenKind::Identifier(name) => Expr::new(ExprKind::Identifier(name), token.span),
+                TokenKind::Function { name, arguments } => {
+                    if operands.len() < arguments {
+                        return Err(CalcError::Syntax { kind: SyntaxErrorKind::MissingOperand, span: token.span });
+                    }
+                    let arguments = operands.split_off(operands.len() - arguments);
+                    Expr::new(ExprKind::Call { name, arguments }, token.span)
+                }
+                TokenKind::Matrix { rows } => {
+                    let count = rows.iter().sum::<usize>();
+                    if operands.len() < count {
+                        return Err(CalcError::Syntax { kind: SyntaxErrorKind::MissingOperand, span: token.span });
+                    }
+                    if rows.iter().any(|&columns| columns != rows[0]) {
+                        return Err(CalcError::Syntax { kind: SyntaxErrorKind::RaggedMatrix, span: token.span });
+                    }
+                    let mut elements = operands.split_off(operands.len() - count).into_iter();
+                    let rows = rows.iter().map(|&columns| elements.by_ref().take(columns).collect()).collect();
+                    Expr::new(ExprKind::Matrix { rows }, token.span)
+                }
+                TokenKind::Assign => {
+                    return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedToken, span: token.span });
+                }
+                TokenKind::Comma | TokenKind::LeftParenthesis | TokenKind::RightParenthesis => {
+                    return Err(CalcError::Syntax { kind: SyntaxErrorKind::UnbalancedParenthesis, span: token.span });
+                }
+            };
+            operands.push(node);
+        }
+        match (operands.pop(), operands.last()) {
+            (Some(tree), None) => Ok(tree),
+            (Some(tree), Some(_)) => Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedToken, span: tree.span }),
+            (None, _) => Err(CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedEnd, span: Span::new(0, 0) }),
+        }
+    }
+
+    pub(crate) fn evaluate<N: Number>(&self, expr: &Expr, scope: &Scope<N>) -> Result<N, CalcError> {
+        match &expr.kind {
+            ExprKind::Number(literal) => N::parse_literal(literal)
+                .ok_or(CalcError::Lex { kind: LexErrorKind::InvalidNumber, span: expr.span }),
+            ExprKind::Unary { operator, operand } => {
+                let operand = self.evaluate::<N>(operand, scope)?;
+                get_unary_operation_result(operand, *operator, self.angle_mode, expr.span)
+            }
+            ExprKind::Binary { operator, left, right } => {
+                let left_value = self.evaluate::<N>(left, scope)?;
+                let right_value = self.evaluate::<N>(right, scope)?;
+                get_operation_result(left_value, right_value, *operator, self.angle_mode, expr.span, right.span)
+            }
+            ExprKind::Factorial { operand } => {
+                let operand = self.evaluate::<N>(operand, scope)?;
+                if !operand.is_dimensionless() {
+                    let message = String::from("Factorial needs a plain number");
+                    return Err(CalcError::Dimension { message, span: expr.span });
+                }
+                functions::factorial(&operand).map_err(|error| error.at(expr.span))
+            }
+            ExprKind::Identifier(name) => {
+                if let Some(value) = scope.get(name) {
+                    return Ok(value);
+                }
+                match self.variables.get(name) {
+                    Some(value) => value_as(value, &format!("Complex value {}", name), expr.span),
+                    None if name == IMAGINARY && N::imaginary_unit().is_none() => Err(complex_only("The imaginary unit").at(expr.span)),
+                    None => match functions::constant(name) {
+                        Some(value) => value.map_err(|error| error.at(expr.span)),
+                        None => self.units.get(name).and_then(N::from_unit)
+                            .ok_or_else(|| CalcError::UnknownName { name: name.clone(), span: expr.span }),
+                    },
+                }
+            }
+            ExprKind::Call { name, arguments } => {
+                if let Some(calculus) = Calculus::from_name(name) {
+                    return self.evaluate_calculus(calculus, name, arguments, expr.span, scope);
+                }
+                let Some(function) = Function::from_name(name) else {
+                    return self.call_user_function(name, arguments, expr.span, scope);
+                };
+                self.check_arity(name, function.arity(), arguments.len(), expr.span)?;
+                let values = arguments.iter()
+                    .map(|argument| self.evaluate(argument, scope))
+                    .collect::<Result<Vec<N>, CalcError>>()?;
+                if !function.accepts_units() && values.iter().any(|value| !value.is_dimensionless()) {
+                    let message = format!("{} needs a plain number", name);
+                    return Err(CalcError::Dimension { message, span: expr.span });
+                }
+                function.apply(&values, self.angle_mode).map_err(|error| error.at(expr.span))
+            }
+            ExprKind::Matrix { rows } => {
+                let elements = rows.iter()
+                    .flatten()
+                    .map(|element| self.evaluate(element, scope))
+                    .collect::<Result<Vec<N>, CalcError>>()?;
+                N::checked_matrix(rows.len(), rows[0].len(), elements).map_err(|error| error.at(expr.span))
+            }
+        }
+    }
+
+    fn compile_node(&self, expr: &Expr, program: &mut Program, parameters: &[String], frame: usize, depth: usize) -> Result<(), CalcError> {
+        let unsupported = |what: &str| CalcError::Domain { message: format!("{} cannot be compiled", what), span: expr.span };
+        match &expr.kind {
+            ExprKind::Number(literal) => {
+                let value = <f64 as Number>::parse_literal(literal)
+                    .ok_or(CalcError::Lex { kind: LexErrorKind::InvalidNumber, span: expr.span })?;
+                program.push(Instruction::Constant(value), expr.span)
+            }
+            ExprKind::Identifier(name) => {
+                if let Some(index) = parameters.iter().position(|parameter| parameter == name) {
+                    return program.push(Instruction::Copy(frame + index), expr.span);
+                }
+                let instruction = match self.variables.get(name) {
+                    Some(value) => Instruction::Constant(value_as(value, &format!("Complex value {}", name), expr.span)?),
+                    None if name == IMAGINARY => return Err(complex_only("The imaginary unit").at(expr.span)),
+                    None if self.units.contains_key(name) => return Err(unsupported(&format!("Unit {}", name))),
+                    None => match functions::constant::<f64>(name) {
+                        Some(value) => Instruction::Constant(value.map_err(|error| error.at(expr.span))?),
+                        None => Instruction::Input(program.input(name)),
+                    },
+                };
+                program.push(instruction, expr.span)
+            }
+            ExprKind::Unary { operator: UnaryOperator::Plus, operand } => self.compile_node(operand, program, parameters, frame, depth),
+            ExprKind::Unary { operator: UnaryOperator::Not, .. } => Err(unsupported("~")),
+            ExprKind::Unary { operator, operand } => {
+                self.compile_node(operand, program, parameters, frame, depth)?;
+                program.push(Instruction::Unary { operator: *operator, span: expr.span }, expr.span)
+            }
+            ExprKind::Binary { operator: Operator::Equation, .. } => {
+                Err(CalcError::Syntax { kind: SyntaxErrorKind::MisplacedEquation, span: expr.span })
+            }
+            ExprKind::Binary { operator: Operator::Bitwise(_), .. } => Err(unsupported("Bitwise operators")),
+            ExprKind::Binary { operator: Operator::Convert, .. } => Err(unsupported("Unit conversion")),
+            ExprKind::Binary { operator: Operator::Polar, .. } => Err(unsupported("Polar form")),
+            ExprKind::Binary { operator, left, right } => {
+                self.compile_node(left, program, parameters, frame, depth)?;
+                self.compile_node(right, program, parameters, frame, depth)?;
+                program.push(Instruction::Binary { operator: *operator, span: expr.span, divisor_span: right.span }, expr.span)
+            }
+            ExprKind::Factorial { operand } => {
+                self.compile_node(operand, program, parameters, frame, depth)?;
+                program.push(Instruction::Factorial { span: expr.span }, expr.span)
+            }
+            ExprKind::Call { name, arguments } => {
+                if Calculus::from_name(name).is_some() {
+                    return Err(unsupported(name));
+                }
+                if let Some(function) = Function::from_name(name) {
+                    if matches!(function, Function::Amort | Function::Linreg | Function::Corr | Function::Simplify) {
+                        return Err(unsupported(name));
+                    }
+                    self.check_arity(name, function.arity(), arguments.len(), expr.span)?;
+                    for argument in arguments {
+                        self.compile_node(argument, program, parameters, frame, depth)?;
+                    }
+                    let instruction = Instruction::Call { function, arguments: arguments.len(), span: expr.span };
+                    return program.push(instruction, expr.span);
+                }
+                let function = self.user_functions.get(name)
+                    .ok_or_else(|| CalcError::UnknownName { name: name.clone(), span: expr.span })?;
+                self.check_arity(name, Arity::Exact(function.parameters.len()), arguments.len(), expr.span)?;
+                if depth >= MAX_CALL_DEPTH {
+                    return Err(CalcError::RecursionLimit { name: name.clone(), span: expr.span });
+                }
+                let base = program.depth();
+                for argument in arguments {
+                    self.compile_node(argument, program, parameters, frame, depth)?;
+                }
+                self.compile_node(&function.body, program, &function.parameters, base, depth + 1)
+                    .map_err(|error| error.with_span(expr.span))?;
+                if arguments.is_empty() {
+                    return Ok(());
+                }
+                program.push(Instruction::Unwind { count: arguments.len() }, expr.span)
+            }
+            ExprKind::Matrix { .. } => Err(unsupported("Matrices")),
+        }
+    }
+
+    fn evaluate_calculus<N: Number>(&self, calculus: Calculus, name: &str, arguments: &[Expr], span: Span, scope: &Scope<N>) -> Result<N, CalcError> {
+        self.check_arity(name, calculus.arity(), arguments.len(), span)?;
+        let variable_index = calculus.variable_index(arguments.len());
+        let variable = match variable_index {
+            Some(index) => self.bound_variable(&arguments[index])?,
+            None => DEFAULT_VARIABLE,
+        };
+        let parameters = [variable.to_string()];
+        let mut operands = arguments.iter().enumerate()
+            .filter(|(index, _)| Some(*index) != variable_index)
+            .map(|(_, argument)| argument);
+        if calculus == Calculus::Sum {
+            let (Some(from), Some(to), Some(body)) = (operands.next(), operands.next(), operands.next()) else {
+                return Err(CalcError::Syntax { kind: SyntaxErrorKind::MissingOperand, span });
+            };
+            return self.evaluate_sum(from, to, body, &parameters, span, scope);
+        }
+        let Some(body) = operands.next() else {
+            return Err(CalcError::Syntax { kind: SyntaxErrorKind::MissingOperand, span });
+        };
+        let mut sample = |x: f64| -> Result<f64, CalcError> {
+            let value = N::from_f64(x).ok_or(CalcError::Overflow { span })?;
+            let scope = scope.bind(&parameters, vec![value]);
+            let value = match &body.kind {
+                ExprKind::Binary { operator: Operator::Equation, left, right } if calculus == Calculus::Solve => {
+                    let left_value = self.evaluate(left, &scope)?;
+                    let right_value = self.evaluate(right, &scope)?;
+                    get_operation_result(left_value, right_value, Operator::Sub, self.angle_mode, body.span, right.span)?
+                }
+                _ => self.evaluate(body, &scope)?,
+            };
+            self.plain(value, name, span)
+        };
+        let bounds = operands
+            .map(|operand| self.evaluate(operand, scope).and_then(|value| self.plain(value, name, operand.span)))
+            .collect::<Result<Vec<f64>, CalcError>>()?;
+        let result = match (calculus, bounds.as_slice()) {
+            (Calculus::Solve, bounds) => calculus::solve(&mut sample, bounds.first().copied())?,
+            (Calculus::Integrate, &[a, b]) => calculus::integrate(&mut sample, a, b)?,
+            (Calculus::Derive, &[at]) => calculus::derive(&mut sample, at)?,
+            _ => return Err(CalcError::Syntax { kind: SyntaxErrorKind::MissingOperand, span }),
+        };
+        let result = result.ok_or_else(|| CalcError::Convergence { name: name.to_string(), span })?;
+        N::from_f64(result).ok_or(CalcError::Overflow { span })
+    }
+
+    fn evaluate_sum<N: Number>(&self, from: &Expr, to: &Expr, body: &Expr, parameters: &[String], span: Span, scope: &Scope<N>) -> Result<N, CalcError> {
+        let mut index = self.evaluate(from, scope)?;
+        let last = self.evaluate(to, scope)?;
+        for bound in [&index, &last] {
+            if !bound.is_dimensionless() || !bound.is_integer() {
+                let message = String::from("Summation bounds must be whole numbers");
+                return Err(CalcError::Domain { message, span });
+            }
+        }
+        let count = last.to_f64() - index.to_f64() + 1.0;
+        if count > MAX_TERMS {
+            let message = String::from("Too many terms in sum");
+            return Err(CalcError::Domain { message, span });
+        }
+        let mut total: Option<N> = None;
+        for _ in 0..count.max(0.0) as usize {
+            let term = self.evaluate(body, &scope.bind(parameters, vec![index.clone()]))?;
+            total = Some(match total {
+                Some(total) => get_operation_result(total, term, Operator::Add, self.angle_mode, span, body.span)?,
+                None => term,
+            });
+            index = get_operation_result(index, N::one(), Operator::Add, self.angle_mode, span, span)?;
+        }
+        Ok(total.unwrap_or_else(N::zero))
+    }
+
+    fn bound_variable<'e>(&self, expr: &'e Expr) -> Result<&'e str, CalcError> {
+        match &expr.kind {
+            ExprKind::Identifier(name) => Ok(name),
+            _ => Err(CalcError::Syntax { kind: SyntaxErrorKind::ExpectedVariable, span: expr.span }),
+        }
+    }
+
+    fn plain<N: Number>(&self, value: N, name: &str, span: Span) -> Result<f64, CalcError> {
+        if !value.is_dimensionless() {
+            let message = format!("{} needs a plain number", name);
+            return Err(CalcError::Dimension { message, span });
+        }
+        if !value.is_scalar() {
+            let message = format!("{} needs a scalar", name);
+            return Err(CalcError::Dimension { message, span });
+        }
+        if !value.is_real() {
+            let message = format!("{} needs a real number", name);
+            return Err(CalcError::Domain { message, span });
+        }
+        Ok(value.to_f64())
+    }
+
+    fn check_arity(&self, name: &str, expected: Arity, found: usize, span: Span) -> Result<(), CalcError> {
+        if expected.accepts(found) {
+            Ok(())
+        }
+        else {
+            Err(CalcError::Arity { name: name.to_string(), expected, found, span })
+        }
+    }
+
+    fn call_user_function<N: Number>(&self, name: &str, arguments: &[Expr], span: Span, scope: &Scope<N>) -> Result<N, CalcError> {
+        let function = self.user_functions.get(name)
+            .ok_or_else(|| CalcError::UnknownName { name: name.to_string(), span })?;
+        self.check_arity(name, Arity::Exact(function.parameters.len()), arguments.len(), span)?;
+        if scope.depth() >= MAX_CALL_DEPTH {
+            return Err(CalcError::RecursionLimit { name: name.to_string(), span });
+        }
+        let values = arguments.iter()
+            .map(|argument| self.evaluate(argument, scope))
+            .collect::<Result<Vec<N>, CalcError>>()?;
+        self.evaluate(&function.body, &scope.call(function, values))
+            .map_err(|error| error.with_span(span))
+    }
+}
+
+fn get_operation_result<N: Number>(a: N, b: N, operator: Operator, angle_mode: AngleMode, span: Span, divisor_span: Span) -> Result<N, CalcError> {
+    let result = match operator {
+        Operator::Mul => a.checked_mul(&b),
+        Operator::Div => {
+            if b.is_zero() {
+                return Err(CalcError::DivisionByZero { span: divisor_span });
+            }
+            a.checked_div(&b)
+        }
+        Operator::Pow => {
+            if a.is_zero() && b.is_negative() {
+                return Err(CalcError::DivisionByZero { span: divisor_span });
+            }
+            a.checked_pow(&b)
+        }
+        Operator::ElementMul => a.checked_element_mul(&b),
+        Operator::ElementDiv => {
+            if b.is_zero() {
+                return Err(CalcError::DivisionByZero { span: divisor_span });
+            }
+            a.checked_element_div(&b)
+        }
+        Operator::Add => a.checked_add(&b),
+        Operator::Sub => a.checked_sub(&b),
+        Operator::Bitwise(operator) => a.checked_bitwise(&b, operator),
+        Operator::Convert => {
+            if b.is_zero() {
+                return Err(CalcError::DivisionByZero { span: divisor_span });
+            }
+            a.checked_convert(&b)
+        }
+        Operator::Polar => a.checked_polar(&b, angle_mode),
+        Operator::Equation => return Err(CalcError::Syntax { kind: SyntaxErrorKind::MisplacedEquation, span }),
+    };
+    result.map_err(|error| error.at(span))
+}
+
+fn get_unary_operation_result<N: Number>(a: N, operator: UnaryOperator, angle_mode: AngleMode, span: Span) -> Result<N, CalcError> {
+    match operator {
+        UnaryOperator::Minus => Ok(a.negate()),
+        UnaryOperator::Plus => Ok(a),
+        UnaryOperator::Not => a.checked_not().map_err(|error| error.at(span)),
+        UnaryOperator::Degree if angle_mode == AngleMode::Degrees => Ok(a),
+        UnaryOperator::Percent if N::is_integer_only() => Err(N::inexact("%").unwrap_or(NumberError::Overflow).at(span)),
+        UnaryOperator::Percent => {
+            let hundred = N::from_f64(100.0).ok_or(CalcError::Overflow { span })?;
+            a.checked_div(&hundred).map_err(|error| error.at(span))
+        }
+        UnaryOperator::Degree => {
+            let radians = functions::approximation::<N>(std::f64::consts::PI / 180.0, "°").map_err(|error| error.at(span))?;
+            a.checked_mul(&radians).map_err(|error| error.at(span))
+        }
+    }
+}
+
+fn check_tables(expr: &Expr, top_level: bool) -> Result<(), CalcError> {
+    let children = match &expr.kind {
+        ExprKind::Number(_) | ExprKind::Identifier(_) => Vec::new(),
+        ExprKind::Unary { operand, .. } | ExprKind::Factorial { operand } => vec![operand.as_ref()],
+        ExprKind::Binary { left, right, .. } => vec![left.as_ref(), right.as_ref()],
+        ExprKind::Call { name, arguments } => {
+            if !top_level && Function::from_name(name) == Some(Function::Amort) {
+                let message = format!("{} returns a table and cannot be used as an operand", name);
+                return Err(CalcError::Domain { message, span: expr.span });
+            }
+            arguments.iter().collect()
+        }
+        ExprKind::Matrix { rows } => rows.iter().flatten().collect(),
+    };
+    children.into_iter().try_for_each(|child| check_tables(child, false))
+}
+
+fn is_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_alphabetic()) && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn operand_names(count: usize) -> Vec<String> {
+    (0..count).map(|index| format!("{}{}", OPERAND, index)).collect()
+}
+
+fn operand(index: usize, span: Span) -> Expr {
+    Expr::new(ExprKind::Identifier(format!("{}{}", OPERAND, index)), span)
+}
+
+fn value_as<N: Number>(value: &Value, what: &str, span: Span) -> Result<N, CalcError> {
+    match value {
+        Value::Complex(complex) if !complex.is_real() && N::imaginary_unit().is_none() => Err(complex_only(what).at(span)),
+        value => N::from_value(value).ok_or(CalcError::Overflow { span }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AngleMode, CalcError, Calculator, LexErrorKind, Span, SyntaxErrorKind};
+
+    fn value(calculator: &mut Calculator, input: &str) -> String {
+        let result = calculator.calculate(input).unwrap_or_else(|error| panic!("{} gave {}", input, error));
+        result.map(|value| value.to_string()).unwrap_or_default()
+    }
+
+    fn error(calculator: &mut Calculator, input: &str) -> CalcError {
+        match calculator.calculate(input) {
+            Err(error) => error,
+            Ok(result) => panic!("{} gave {:?}", input, result),
+        }
+    }
+
+    #[test]
+    fn groups_with_parentheses() {
+        let mut calculator = Calculator::default();
+        assert_eq!(value(&mut calculator, "(2 + 3) * 4"), "20");
+        assert_eq!(value(&mut calculator, "((1 + 2) * (3 + 4)) / 7"), "3");
+        for (input, kind, start) in [
+            ("(2 + 3", SyntaxErrorKind::UnbalancedParenthesis, 0),
+            ("2 + 3)", SyntaxErrorKind::UnbalancedParenthesis, 5),
+            ("()", SyntaxErrorKind::EmptyParentheses, 1),
+        ] {
+            assert_eq!(error(&mut calculator, input), CalcError::Syntax { kind, span: Span::new(start, start + 1) }, "{}", input);
+        }
+    }
+
+    #[test]
+    fn binds_unary_minus_below_powers() {
+        let mut calculator = Calculator::default();
+        for (input, expected) in [("-2^2", "-4"), ("(-2)^2", "4"), ("2 * -3", "-6"), ("-5 + 3", "-2"), ("--2", "2"), ("2^-1", "0.5"), ("+4 - +1", "3")] {
+            assert_eq!(value(&mut calculator, input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn follows_operator_precedence_and_associativity() {
+        let mut calculator = Calculator::default();
+        for (input, expected) in [("2 + 3 * 4", "14"), ("10 - 4 - 3", "3"), ("8 / 4 / 2", "1"), ("2^3^2", "512"), ("2 * 3^2", "18"), ("3!^2", "36")] {
+            assert_eq!(value(&mut calculator, input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn points_errors_at_their_source() {
+        let mut calculator = Calculator::default();
+        assert_eq!(error(&mut calculator, "5+"), CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedEnd, span: Span::new(2, 2) });
+        assert_eq!(error(&mut calculator, "1 +* 2"), CalcError::Syntax { kind: SyntaxErrorKind::UnexpectedToken, span: Span::new(3, 4) });
+        assert_eq!(error(&mut calculator, "2 $ 3"), CalcError::Lex { kind: LexErrorKind::UnexpectedCharacter('$'), span: Span::new(2, 3) });
+        assert_eq!(error(&mut calculator, "1 / 0"), CalcError::DivisionByZero { span: Span::new(4, 5) });
+        assert_eq!(error(&mut calculator, "5 + y").span(), Span::new(4, 5));
+    }
+
+    #[test]
+    fn evaluates_functions_and_constants() {
+        let mut calculator = Calculator::default();
+        for (input, expected) in [
+            ("sin(pi / 2)", "1"),
+            ("cos(0) + ln(e)", "2"),
+            ("log(2, 8)", "3"),
+            ("sqrt(16) + abs(-2)", "6"),
+            ("floor(2.7) + ceil(2.1) + round(2.5)", "8"),
+            ("max(1, 5, 3) - min(4, 2)", "3"),
+            ("4!", "24"),
+        ] {
+            assert_eq!(value(&mut calculator, input), expected, "{}", input);
+        }
+        assert!(matches!(error(&mut calculator, "sqrt(-1)"), CalcError::Domain { .. }));
+        assert!(matches!(error(&mut calculator, "2.5!"), CalcError::Domain { .. }));
+        calculator.set_angle_mode(AngleMode::Degrees);
+        assert_eq!(value(&mut calculator, "sin(90) + asin(1)"), "91");
+    }
+
+    #[test]
+    fn keeps_variables_and_the_last_answer() {
+        let mut calculator = Calculator::default();
+        assert_eq!(value(&mut calculator, "rate = 0.5"), "0.5");
+        assert_eq!(value(&mut calculator, "100 * (1 + rate)^2"), "225");
+        assert_eq!(value(&mut calculator, "ans - 25"), "200");
+        assert_eq!(value(&mut calculator, "rate = rate * 2"), "1");
+        assert_eq!(calculator.variables().get("rate").map(ToString::to_string).as_deref(), Some("1"));
+        calculator.remove_variable("rate");
+        assert!(matches!(error(&mut calculator, "rate + 1"), CalcError::UnknownName { .. }));
+        for input in ["sin = 3", "pi = 3"] {
+            assert!(matches!(error(&mut calculator, input), CalcError::ReservedName { .. }), "{}", input);
+        }
+    }
+
+    #[test]
+    fn calls_user_functions() {
+        let mut calculator = Calculator::default();
+        assert_eq!(calculator.calculate("f(x, y) = x^2 + y").unwrap(), None);
+        assert_eq!(value(&mut calculator, "f(3, 1) + f(1, 0)"), "11");
+        assert_eq!(calculator.calculate("g(x) = f(x, x)").unwrap(), None);
+        assert_eq!(value(&mut calculator, "g(2)"), "6");
+        let arity = error(&mut calculator, "f(1)");
+        assert_eq!(arity.message(), "f expects 2 arguments, got 1");
+        assert_eq!(arity.span(), Span::new(0, 4));
+        calculator.calculate("loop(n) = loop(n - 1)").unwrap();
+        assert!(matches!(error(&mut calculator, "loop(1)"), CalcError::RecursionLimit { .. }));
+        assert!(matches!(error(&mut calculator, "h(1)"), CalcError::UnknownName { .. }));
+        assert!(matches!(error(&mut calculator, "k(x, x) = x"), CalcError::Syntax { kind: SyntaxErrorKind::DuplicateParameter, .. }));
+    }
+
+    #[test]
+    fn solves_integrates_and_derives() {
+        let mut calculator = Calculator::default();
+        let close = |calculator: &mut Calculator, input: &str, expected: f64| {
+            let result = calculator.evaluate_expression(input).unwrap_or_else(|error| panic!("{} gave {}", input, error));
+            assert!((result.to_f64() - expected).abs() < 1e-9, "{} gave {}", input, result);
+        };
+        close(&mut calculator, "solve(x^2 - 2 = 0, x)", std::f64::consts::SQRT_2);
+        close(&mut calculator, "solve(cos(t) = t, t, 1)", 0.7390851332151607);
+        close(&mut calculator, "integrate(x^2, 0, 3)", 9.0);
+        close(&mut calculator, "derive(x^3, x, 2)", 12.0);
+        assert_eq!(value(&mut calculator, "sum(i, 1, 10, i^2)"), "385");
+        for input in ["solve(x^2 + 1 = 0, x)", "integrate(tan(x), 1, 2)"] {
+            assert!(matches!(error(&mut calculator, input), CalcError::Convergence { .. }), "{}", input);
+        }
+        assert!(matches!(error(&mut calculator, "x + 1"), CalcError::UnknownName { .. }));
+    }
+
+    #[test]
+    fn computes_with_matrices() {
+        let mut calculator = Calculator::default();
+        for (input, expected) in [
+            ("[1, 2; 3, 4] * [5; 6]", "[17; 39]"),
+            ("[1, 2] .* [3, 4] + [1, 1]", "[4, 9]"),
+            ("2 * [1, 2; 3, 4] - [1, 0; 0, 1]", "[1, 4; 6, 7]"),
+            ("transpose([1, 2; 3, 4])", "[1, 3; 2, 4]"),
+            ("det([1, 2; 3, 4])", "-2"),
+            ("inv([1, 2; 3, 4])", "[-2, 1; 1.5, -0.5]"),
+            ("linsolve([2, 1; 1, 3], [3; 5])", "[0.8; 1.4]"),
+        ] {
+            assert_eq!(value(&mut calculator, input), expected, "{}", input);
+        }
+        for input in ["[1, 2] + [1, 2, 3]", "[1, 2; 3, 4] * [1, 2]", "det([1, 2])"] {
+            assert!(matches!(error(&mut calculator, input), CalcError::Dimension { .. }), "{}", input);
+        }
+        assert_eq!(error(&mut calculator, "inv([1, 2; 2, 4])").message(), "Matrix is singular");
+        assert!(matches!(error(&mut calculator, "[1, 2; 3]"), CalcError::Syntax { kind: SyntaxErrorKind::RaggedMatrix, .. }));
+    }
+}
@@ -0,0 +1,430 @@
+use std::cmp::Ordering;
+
+use super::error::CalcError;
+use super::finance;
+use super::functions::{AngleMode, Function};
+use super::number::{Number, NumberError, Value};
+use super::statistics;
+use super::token::BitwiseOperator;
+use super::units::Unit;
+
+const SINGULAR_TOLERANCE: f64 = 1e-12;
+const MAX_MATRIX_POWER: f64 = 1024.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Matrix<N> {
+    pub(crate) rows: usize,
+    pub(crate) columns: usize,
+    pub(crate) elements: Vec<N>,
+}
+
+impl<N> Matrix<N> {
+    pub(crate) fn into_value(self, element: impl FnMut(N) -> Result<Value, CalcError>) -> Result<Value, CalcError> {
+        let (rows, columns) = (self.rows, self.columns);
+        let mut elements = self.elements.into_iter().map(element).collect::<Result<Vec<Value>, CalcError>>()?;
+        if rows == 1 && columns == 1 {
+            Ok(elements.remove(0))
+        }
+        else {
+            Ok(Value::Matrix { rows, columns, elements })
+        }
+    }
+}
+
+impl<N: Number> Matrix<N> {
+    fn scalar(value: N) -> Self {
+        Matrix { rows: 1, columns: 1, elements: vec![value] }
+    }
+
+    fn identity(size: usize) -> Self {
+        let elements = (0..size * size)
+            .map(|index| if index % (size + 1) == 0 { N::one() } else { N::zero() })
+            .collect();
+        Matrix { rows: size, columns: size, elements }
+    }
+
+    fn shape(&self) -> String {
+        format!("{}x{}", self.rows, self.columns)
+    }
+
+    fn get(&self, row: usize, column: usize) -> &N {
+        &self.elements[row * self.columns + column]
+    }
+
+    fn try_map(&self, f: impl FnMut(&N) -> Result<N, NumberError>) -> Result<Self, NumberError> {
+        let elements = self.elements.iter().map(f).collect::<Result<Vec<N>, NumberError>>()?;
+        Ok(Matrix { rows: self.rows, columns: self.columns, elements })
+    }
+
+    fn map(&self, f: impl Fn(&N) -> N) -> Self {
+        Matrix { rows: self.rows, columns: self.columns, elements: self.elements.iter().map(f).collect() }
+    }
+
+    fn zip_with(&self, other: &Self, verb: &str, f: impl Fn(&N, &N) -> Result<N, NumberError>) -> Result<Self, NumberError> {
+        if self.is_scalar() {
+            return other.try_map(|b| f(&self.elements[0], b));
+        }
+        if other.is_scalar() {
+            return self.try_map(|a| f(a, &other.elements[0]));
+        }
+        if self.rows != other.rows || self.columns != other.columns {
+            return Err(NumberError::Dimension(format!("Cannot {} {} and {} matrices", verb, self.shape(), other.shape())));
+        }
+        let elements = self.elements.iter()
+            .zip(&other.elements)
+            .map(|(a, b)| f(a, b))
+            .collect::<Result<Vec<N>, NumberError>>()?;
+        Ok(Matrix { rows: self.rows, columns: self.columns, elements })
+    }
+
+    fn product(&self, other: &Self) -> Result<Self, NumberError> {
+        if self.columns != other.rows {
+            return Err(NumberError::Dimension(format!("Cannot multiply {} and {} matrices", self.shape(), other.shape())));
+        }
+        let mut elements = Vec::with_capacity(self.rows * other.columns);
+        for row in 0..self.rows {
+            for column in 0..other.columns {
+                let mut total = self.get(row, 0).checked_mul(other.get(0, column))?;
+                for index in 1..self.columns {
+                    total = total.checked_add(&self.get(row, index).checked_mul(other.get(index, column))?)?;
+                }
+                elements.push(total);
+            }
+        }
+        Ok(Matrix { rows: self.rows, columns: other.columns, elements })
+    }
+
+    fn transpose(&self) -> Self {
+        let elements = (0..self.columns)
+            .flat_map(|column| (0..self.rows).map(move |row| (row, column)))
+            .map(|(row, column)| self.get(row, column).clone())
+            .collect();
+        Matrix { rows: self.columns, columns: self.rows, elements }
+    }
+
+    fn check_square(&self, name: &str) -> Result<(), NumberError> {
+        if self.rows == self.columns {
+            Ok(())
+        }
+        else {
+            Err(NumberError::Dimension(format!("{} needs a square matrix, not {}", name, self.shape())))
+        }
+    }
+
+    fn reduce(&self, name: &str, right: &Self) -> Result<(N, Option<Self>), NumberError> {
+        if N::is_integer_only() {
+            return Err(NumberError::Domain(format!("{} is not available in programmer mode", name)));
+        }
+        self.check_square(name)?;
+        let size = self.rows;
+        let mut rows = (0..size)
+            .map(|row| {
+                let mut values = self.elements[row * size..(row + 1) * size].to_vec();
+                values.extend_from_slice(&right.elements[row * right.columns..(row + 1) * right.columns]);
+                values
+            })
+            .collect::<Vec<Vec<N>>>();
+        let scale = self.elements.iter().map(magnitude).fold(0.0, f64::max);
+        let mut determinant = N::one();
+        for column in 0..size {
+            let pivot = (column..size)
+                .max_by(|&a, &b| magnitude(&rows[a][column]).total_cmp(&magnitude(&rows[b][column])))
+                .unwrap_or(column);
+            if rows[pivot][column].is_zero() || magnitude(&rows[pivot][column]) <= SINGULAR_TOLERANCE * scale {
+                return Ok((N::zero(), None));
+            }
+            if pivot != column {
+                rows.swap(pivot, column);
+                determinant = determinant.negate();
+            }
+            let pivot_value = rows[column][column].clone();
+            determinant = determinant.checked_mul(&pivot_value)?;
+            for value in rows[column].iter_mut() {
+                *value = value.checked_div(&pivot_value)?;
+            }
+            let pivot_row = rows[column].clone();
+            for (index, row) in rows.iter_mut().enumerate() {
+                if index == column || row[column].is_zero() {
+                    continue;
+                }
+                let factor = row[column].clone();
+                for (value, pivot) in row.iter_mut().zip(&pivot_row).skip(column) {
+                    *value = value.checked_sub(&factor.checked_mul(pivot)?)?;
+                }
+            }
+        }
+        let elements = rows.into_iter().flat_map(|row| row.into_iter().skip(size)).collect();
+        Ok((determinant, Some(Matrix { rows: size, columns: right.columns, elements })))
+    }
+
+    fn apply(function: Function, arguments: &[Self], angle_mode: AngleMode) -> Result<Self, NumberError> {
+        let matrix = &arguments[0];
+        match function {
+            Function::Transpose => Ok(matrix.transpose()),
+            Function::Det => {
+                let empty = Matrix { rows: matrix.rows, columns: 0, elements: Vec::new() };
+                matrix.reduce("det", &empty).map(|(determinant, _)| Matrix::scalar(determinant))
+            }
+            Function::Inv => {
+                matrix.reduce("inv", &Matrix::identity(matrix.rows))?.1.ok_or_else(singular)
+            }
+            Function::Linsolve => {
+                let right = &arguments[1];
+                if right.rows != matrix.rows {
+                    return Err(NumberError::Dimension(format!(
+                        "Cannot solve a {} system for a {} right-hand side", matrix.shape(), right.shape()
+                    )));
+                }
+                matrix.reduce("linsolve", right)?.1.ok_or_else(singular)
+            }
+            Function::Percentile => {
+                let percent = &arguments[1];
+                if !percent.is_scalar() {
+                    return Err(NumberError::Dimension(String::from("Percentile must be a scalar")));
+                }
+                statistics::percentile(&matrix.elements, &percent.elements[0]).map(Matrix::scalar)
+            }
+            Function::Linreg => {
+                let (slope, intercept) = statistics::regression(&matrix.elements, &arguments[1].elements)?;
+                Ok(Matrix { rows: 1, columns: 2, elements: vec![slope, intercept] })
+            }
+            Function::Corr => statistics::correlation(&matrix.elements, &arguments[1].elements).map(Matrix::scalar),
+            Function::Npv | Function::Irr => {
+                let skipped = if function == Function::Npv { 1 } else { 0 };
+                let mut flows = arguments[skipped..].iter().flat_map(|argument| argument.elements.iter().cloned()).collect::<Vec<N>>();
+                if function == Function::Npv {
+                    if !matrix.is_scalar() {
+                        return Err(NumberError::Dimension(String::from("Rate must be a scalar")));
+                    }
+                    flows.insert(0, matrix.elements[0].clone());
+                }
+                function.apply(&flows, angle_mode).map(Matrix::scalar)
+            }
+            _ if function.is_financial() => {
+                if arguments.iter().any(|argument| !argument.is_scalar()) {
+                    return Err(NumberError::Dimension(String::from("Financial functions need scalar arguments")));
+                }
+                let values = arguments.iter().map(|argument| argument.elements[0].clone()).collect::<Vec<N>>();
+                if function != Function::Amort {
+                    return function.apply(&values, angle_mode).map(Matrix::scalar);
+                }
+                let schedule = finance::amortization(&values[0], &values[1], &values[2])?;
+                Ok(Matrix { rows: schedule.len(), columns: 5, elements: schedule.into_iter().flatten().collect() })
+            }
+            _ if function.is_aggregate() => {
+                let elements = arguments.iter().flat_map(|argument| argument.elements.iter().cloned()).collect::<Vec<N>>();
+                function.apply(&elements, angle_mode).map(Matrix::scalar)
+            }
+            _ => match arguments {
+                [left, right] => left.zip_with(right, "combine", |a, b| function.apply(&[a.clone(), b.clone()], angle_mode)),
+                _ => matrix.try_map(|value| function.apply(std::slice::from_ref(value), angle_mode)),
+            },
+        }
+    }
+}
+
+fn magnitude<N: Number>(value: &N) -> f64 {
+    value.abs().to_f64()
+}
+
+fn singular() -> NumberError {
+    NumberError::Domain(String::from("Matrix is singular"))
+}
+
+impl<N: Number> PartialOrd for Matrix<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self.is_scalar() && other.is_scalar() { self.elements[0].partial_cmp(&other.elements[0]) } else { None }
+    }
+}
+
+impl<N: Number> Number for Matrix<N> {
+    fn parse_literal(literal: &str) -> Option<Self> {
+        N::parse_literal(literal).map(Self::scalar)
+    }
+
+    fn from_f64(value: f64) -> Option<Self> {
+        N::from_f64(value).map(Self::scalar)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Matrix { rows, columns, elements } => {
+                let elements = elements.iter().map(N::from_value).collect::<Option<Vec<N>>>()?;
+                Some(Matrix { rows: *rows, columns: *columns, elements })
+            }
+            value => N::from_value(value).map(Self::scalar),
+        }
+    }
+
+    fn from_unit(unit: &Unit) -> Option<Self> {
+        N::from_unit(unit).map(Self::scalar)
+    }
+
+    fn to_f64(&self) -> f64 {
+        if self.is_scalar() { self.elements[0].to_f64() } else { f64::NAN }
+    }
+
+    fn zero() -> Self {
+        Self::scalar(N::zero())
+    }
+
+    fn one() -> Self {
+        Self::scalar(N::one())
+    }
+
+    fn checked_add(&self, other: &Self) -> Result<Self, NumberError> {
+        self.zip_with(other, "add", N::checked_add)
+    }
+
+    fn checked_sub(&self, other: &Self) -> Result<Self, NumberError> {
+        self.zip_with(other, "subtract", N::checked_sub)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Result<Self, NumberError> {
+        if self.is_scalar() || other.is_scalar() {
+            self.zip_with(other, "multiply", N::checked_mul)
+        }
+        else {
+            self.product(other)
+        }
+    }
+
+    fn checked_div(&self, other: &Self) -> Result<Self, NumberError> {
+        if !other.is_scalar() {
+            return Err(NumberError::Dimension(format!("Cannot divide by a {} matrix, multiply by its inverse instead", other.shape())));
+        }
+        self.zip_with(other, "divide", N::checked_div)
+    }
+
+    fn checked_element_mul(&self, other: &Self) -> Result<Self, NumberError> {
+        self.zip_with(other, "multiply", N::checked_mul)
+    }
+
+    fn checked_element_div(&self, other: &Self) -> Result<Self, NumberError> {
+        self.zip_with(other, "divide", |a, b| if b.is_zero() { Err(NumberError::DivisionByZero) } else { a.checked_div(b) })
+    }
+
+    fn checked_pow(&self, exponent: &Self) -> Result<Self, NumberError> {
+        if !exponent.is_scalar() {
+            return Err(NumberError::Dimension(format!("Exponent must be a scalar, not a {} matrix", exponent.shape())));
+        }
+        if self.is_scalar() {
+            return self.elements[0].checked_pow(&exponent.elements[0]).map(Self::scalar);
+        }
+        self.check_square("Matrix power")?;
+        let power = exponent.to_f64();
+        if !exponent.is_integer() || power.abs() > MAX_MATRIX_POWER {
+            return Err(NumberError::Domain(String::from("Matrix power must be a whole number")));
+        }
+        let mut base = if power < 0.0 { Matrix::apply(Function::Inv, std::slice::from_ref(self), AngleMode::default())? } else { self.clone() };
+        let mut result = Matrix::identity(self.rows);
+        let mut power = power.abs() as u64;
+        while power > 0 {
+            if power & 1 == 1 {
+                result = result.product(&base)?;
+            }
+            base = base.product(&base)?;
+            power >>= 1;
+        }
+        Ok(result)
+    }
+
+    fn negate(&self) -> Self {
+        self.map(N::negate)
+    }
+
+    fn abs(&self) -> Self {
+        self.map(N::abs)
+    }
+
+    fn floor(&self) -> Self {
+        self.map(N::floor)
+    }
+
+    fn ceil(&self) -> Self {
+        self.map(N::ceil)
+    }
+
+    fn round(&self) -> Self {
+        self.map(N::round)
+    }
+
+    fn checked_bitwise(&self, other: &Self, operator: BitwiseOperator) -> Result<Self, NumberError> {
+        self.zip_with(other, "combine", |a, b| a.checked_bitwise(b, operator))
+    }
+
+    fn checked_not(&self) -> Result<Self, NumberError> {
+        self.try_map(N::checked_not)
+    }
+
+    fn checked_convert(&self, unit: &Self) -> Result<Self, NumberError> {
+        self.zip_with(unit, "convert", N::checked_convert)
+    }
+
+    fn checked_polar(&self, angle: &Self, angle_mode: AngleMode) -> Result<Self, NumberError> {
+        self.zip_with(angle, "combine", |a, b| a.checked_polar(b, angle_mode))
+    }
+
+    fn checked_matrix(rows: usize, columns: usize, elements: Vec<Self>) -> Result<Self, NumberError> {
+        let elements = elements.into_iter()
+            .map(|element| {
+                if element.is_scalar() {
+                    Ok(element.elements.into_iter().next().unwrap_or_else(N::zero))
+                }
+                else {
+                    Err(NumberError::Dimension(format!("Matrix elements must be scalars, not {} matrices", element.shape())))
+                }
+            })
+            .collect::<Result<Vec<N>, NumberError>>()?;
+        Ok(Matrix { rows, columns, elements })
+    }
+
+    fn check_compatible(&self, other: &Self) -> Result<(), NumberError> {
+        if self.is_scalar() && other.is_scalar() {
+            self.elements[0].check_compatible(&other.elements[0])
+        }
+        else {
+            Err(NumberError::Dimension(String::from("Matrices cannot be compared")))
+        }
+    }
+
+    fn imaginary_unit() -> Option<Self> {
+        N::imaginary_unit().map(Self::scalar)
+    }
+
+    fn inexact(what: &str) -> Option<NumberError> {
+        N::inexact(what)
+    }
+
+    fn apply_function(function: Function, arguments: &[Self], angle_mode: AngleMode) -> Option<Result<Self, NumberError>> {
+        Some(Matrix::apply(function, arguments, angle_mode))
+    }
+
+    fn is_dimensionless(&self) -> bool {
+        self.elements.iter().all(N::is_dimensionless)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.is_scalar() && self.elements[0].is_zero()
+    }
+
+    fn is_integer(&self) -> bool {
+        self.is_scalar() && self.elements[0].is_integer()
+    }
+
+    fn is_negative(&self) -> bool {
+        self.is_scalar() && self.elements[0].is_negative()
+    }
+
+    fn is_real(&self) -> bool {
+        self.elements.iter().all(N::is_real)
+    }
+
+    fn is_integer_only() -> bool {
+        N::is_integer_only()
+    }
+
+    fn is_scalar(&self) -> bool {
+        self.rows == 1 && self.columns == 1
+    }
+}
@@ -0,0 +1,635 @@
+use std::fmt;
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Pow, Signed, Zero};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::{Decimal, MathematicalOps, RoundingStrategy};
+
+use super::complex::{Complex, ComplexDisplay};
+use super::error::CalcError;
+use super::functions::{AngleMode, Function};
+use super::token::{BitwiseOperator, Span};
+use super::units::{QuantityValue, Unit};
+use super::word::WordSize;
+
+const SIGNIFICANT_DIGITS: usize = 15;
+const MAX_DECIMAL_EXPONENT: i64 = 100_000;
+const MAX_RATIONAL_BITS: u64 = 1 << 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum NumberError {
+    Overflow,
+    DivisionByZero,
+    Domain(String),
+    Dimension(String),
+}
+
+impl NumberError {
+    pub(crate) fn at(self, span: Span) -> CalcError {
+        match self {
+            NumberError::Overflow => CalcError::Overflow { span },
+            NumberError::DivisionByZero => CalcError::DivisionByZero { span },
+            NumberError::Domain(message) => CalcError::Domain { message, span },
+            NumberError::Dimension(message) => CalcError::Dimension { message, span },
+        }
+    }
+}
+
+pub(crate) trait Number: Clone + PartialEq + PartialOrd {
+    fn parse_literal(literal: &str) -> Option<Self>;
+    fn from_f64(value: f64) -> Option<Self>;
+    fn from_value(value: &Value) -> Option<Self>;
+    fn from_unit(_unit: &Unit) -> Option<Self> {
+        None
+    }
+    fn to_f64(&self) -> f64;
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn checked_add(&self, other: &Self) -> Result<Self, NumberError>;
+    fn checked_sub(&self, other: &Self) -> Result<Self, NumberError>;
+    fn checked_mul(&self, other: &Self) -> Result<Self, NumberError>;
+    fn checked_div(&self, other: &Self) -> Result<Self, NumberError>;
+    fn checked_pow(&self, exponent: &Self) -> Result<Self, NumberError>;
+    fn checked_element_mul(&self, other: &Self) -> Result<Self, NumberError> {
+        self.checked_mul(other)
+    }
+    fn checked_element_div(&self, other: &Self) -> Result<Self, NumberError> {
+        self.checked_div(other)
+    }
+    fn negate(&self) -> Self;
+    fn abs(&self) -> Self;
+    fn floor(&self) -> Self;
+    fn ceil(&self) -> Self;
+    fn round(&self) -> Self;
+    fn checked_bitwise(&self, _other: &Self, _operator: BitwiseOperator) -> Result<Self, NumberError> {
+        Err(integer_only())
+    }
+    fn checked_not(&self) -> Result<Self, NumberError> {
+        Err(integer_only())
+    }
+    fn checked_convert(&self, _unit: &Self) -> Result<Self, NumberError> {
+        Err(NumberError::Domain(String::from("Unit conversion is not available in programmer mode")))
+    }
+    fn checked_polar(&self, _angle: &Self, _angle_mode: AngleMode) -> Result<Self, NumberError> {
+        Err(complex_only("Polar form"))
+    }
+    fn checked_matrix(_rows: usize, _columns: usize, _elements: Vec<Self>) -> Result<Self, NumberError> {
+        Err(NumberError::Domain(String::from("Matrices are not supported here")))
+    }
+    fn check_compatible(&self, _other: &Self) -> Result<(), NumberError> {
+        Ok(())
+    }
+    fn imaginary_unit() -> Option<Self> {
+        None
+    }
+    fn apply_function(_function: Function, _arguments: &[Self], _angle_mode: AngleMode) -> Option<Result<Self, NumberError>> {
+        None
+    }
+    fn inexact(_what: &str) -> Option<NumberError> {
+        None
+    }
+    fn is_real(&self) -> bool {
+        true
+    }
+    fn is_scalar(&self) -> bool {
+        true
+    }
+    fn is_integer_only() -> bool {
+        false
+    }
+    fn is_dimensionless(&self) -> bool {
+        true
+    }
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+    fn is_integer(&self) -> bool {
+        self.floor() == *self
+    }
+    fn is_negative(&self) -> bool {
+        *self < Self::zero()
+    }
+}
+
+fn integer_only() -> NumberError {
+    NumberError::Domain(String::from("Bitwise operators need programmer mode"))
+}
+
+pub(crate) fn complex_only(what: &str) -> NumberError {
+    NumberError::Domain(format!("{} needs complex mode", what))
+}
+
+fn radix_prefix(literal: &str) -> Option<(u32, &str)> {
+    match literal.get(..2) {
+        Some("0x" | "0X") => Some((16, &literal[2..])),
+        Some("0o" | "0O") => Some((8, &literal[2..])),
+        Some("0b" | "0B") => Some((2, &literal[2..])),
+        _ => None,
+    }
+}
+
+pub(crate) fn parse_integer(literal: &str) -> Option<i128> {
+    let (radix, digits) = radix_prefix(literal).unwrap_or((10, literal));
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    i128::from_str_radix(digits, radix).ok()
+}
+
+fn finite(value: f64) -> Result<f64, NumberError> {
+    if value.is_nan() {
+        Err(NumberError::Domain(String::from("Result is not a real number")))
+    }
+    else if value.is_infinite() {
+        Err(NumberError::Overflow)
+    }
+    else {
+        Ok(value)
+    }
+}
+
+impl Number for f64 {
+    fn parse_literal(literal: &str) -> Option<Self> {
+        if radix_prefix(literal).is_some() {
+            return parse_integer(literal).map(|value| value as f64);
+        }
+        literal.parse::<f64>().ok().filter(|value| value.is_finite())
+    }
+
+    fn from_f64(value: f64) -> Option<Self> {
+        finite(value).ok()
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        finite(value.to_f64()).ok()
+    }
+
+    fn to_f64(&self) -> f64 {
+        *self
+    }
+
+    fn zero() -> Self {
+        0.0
+    }
+
+    fn one() -> Self {
+        1.0
+    }
+
+    fn checked_add(&self, other: &Self) -> Result<Self, NumberError> {
+        finite(self + other)
+    }
+
+    fn checked_sub(&self, other: &Self) -> Result<Self, NumberError> {
+        finite(self - other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Result<Self, NumberError> {
+        finite(self * other)
+    }
+
+    fn checked_div(&self, other: &Self) -> Result<Self, NumberError> {
+        finite(self / other)
+    }
+
+    fn checked_pow(&self, exponent: &Self) -> Result<Self, NumberError> {
+        finite(self.powf(*exponent))
+    }
+
+    fn negate(&self) -> Self {
+        -self
+    }
+
+    fn abs(&self) -> Self {
+        f64::abs(*self)
+    }
+
+    fn floor(&self) -> Self {
+        f64::floor(*self)
+    }
+
+    fn ceil(&self) -> Self {
+        f64::ceil(*self)
+    }
+
+    fn round(&self) -> Self {
+        f64::round(*self)
+    }
+}
+
+impl Number for Decimal {
+    fn parse_literal(literal: &str) -> Option<Self> {
+        if radix_prefix(literal).is_some() {
+            return parse_integer(literal).and_then(Decimal::from_i128);
+        }
+        if literal.contains(['e', 'E']) {
+            Decimal::from_scientific(literal).ok()
+        }
+        else {
+            Decimal::from_str_exact(literal).ok()
+        }
+    }
+
+    fn from_f64(value: f64) -> Option<Self> {
+        FromPrimitive::from_f64(value).and_then(|value: Decimal| value.round_sf(SIGNIFICANT_DIGITS as u32)).map(|value| value.normalize())
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Float(value) => <Decimal as Number>::from_f64(*value),
+            Value::Decimal(value) => Some(*value),
+            Value::Integer { value, .. } => Decimal::from_i128(*value),
+            Value::Quantity(quantity) => Self::from_value(&quantity.base),
+            Value::Expression(_) | Value::Matrix { .. } => None,
+            Value::Complex(_) => <Decimal as Number>::from_f64(value.to_f64()),
+            Value::Rational(value) => {
+                let exact = value.numer().to_i128().zip(value.denom().to_i128())
+                    .and_then(|(numerator, denominator)| {
+                        Decimal::from_i128(numerator)?.checked_div(Decimal::from_i128(denominator)?)
+                    });
+                exact.or_else(|| <Decimal as Number>::from_f64(Number::to_f64(value)))
+            }
+        }
+    }
+
+    fn to_f64(&self) -> f64 {
+        ToPrimitive::to_f64(self).unwrap_or(f64::NAN)
+    }
+
+    fn zero() -> Self {
+        Decimal::ZERO
+    }
+
+    fn one() -> Self {
+        Decimal::ONE
+    }
+
+    fn checked_add(&self, other: &Self) -> Result<Self, NumberError> {
+        Decimal::checked_add(*self, *other).ok_or(NumberError::Overflow)
+    }
+
+    fn checked_sub(&self, other: &Self) -> Result<Self, NumberError> {
+        Decimal::checked_sub(*self, *other).ok_or(NumberError::Overflow)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Result<Self, NumberError> {
+        Decimal::checked_mul(*self, *other).ok_or(NumberError::Overflow)
+    }
+
+    fn checked_div(&self, other: &Self) -> Result<Self, NumberError> {
+        Decimal::checked_div(*self, *other).ok_or(NumberError::Overflow)
+    }
+
+    fn checked_pow(&self, exponent: &Self) -> Result<Self, NumberError> {
+        if let Some(exponent) = exponent.is_integer().then(|| exponent.to_i64()).flatten() {
+            return self.checked_powi(exponent).ok_or(NumberError::Overflow);
+        }
+        let result = finite(Number::to_f64(self).powf(Number::to_f64(exponent)))?;
+        <Decimal as Number>::from_f64(result).ok_or(NumberError::Overflow)
+    }
+
+    fn negate(&self) -> Self {
+        -*self
+    }
+
+    fn abs(&self) -> Self {
+        Decimal::abs(self)
+    }
+
+    fn floor(&self) -> Self {
+        Decimal::floor(self)
+    }
+
+    fn ceil(&self) -> Self {
+        Decimal::ceil(self)
+    }
+
+    fn round(&self) -> Self {
+        self.round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+    }
+}
+
+impl Number for BigRational {
+    fn parse_literal(literal: &str) -> Option<Self> {
+        if radix_prefix(literal).is_some() {
+            return parse_integer(literal).map(|value| BigRational::from_integer(BigInt::from(value)));
+        }
+        let (mantissa, exponent) = match literal.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().ok()?),
+            None => (literal, 0),
+        };
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let digits = format!("{}{}", integer, fraction).parse::<BigInt>().ok()?;
+        let scale = exponent - fraction.len() as i64;
+        if scale.abs() > MAX_DECIMAL_EXPONENT {
+            return None;
+        }
+        let power = BigInt::from(10).pow(scale.unsigned_abs() as u32);
+        if scale >= 0 {
+            Some(BigRational::from_integer(digits * power))
+        }
+        else {
+            Some(BigRational::new(digits, power))
+        }
+    }
+
+    fn from_f64(value: f64) -> Option<Self> {
+        let value = finite(value).ok()?;
+        Self::parse_literal(&format!("{:.*e}", SIGNIFICANT_DIGITS - 1, value))
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Float(value) => <BigRational as Number>::from_f64(*value),
+            Value::Decimal(value) => Self::parse_literal(&value.to_string()),
+            Value::Rational(value) => Some(value.clone()),
+            Value::Integer { value, .. } => Some(BigRational::from_integer(BigInt::from(*value))),
+            Value::Quantity(quantity) => Self::from_value(&quantity.base),
+            Value::Complex(_) => <BigRational as Number>::from_f64(value.to_f64()),
+            Value::Expression(_) | Value::Matrix { .. } => None,
+        }
+    }
+
+    fn to_f64(&self) -> f64 {
+        ToPrimitive::to_f64(self).unwrap_or(f64::NAN)
+    }
+
+    fn zero() -> Self {
+        Zero::zero()
+    }
+
+    fn one() -> Self {
+        One::one()
+    }
+
+    fn checked_add(&self, other: &Self) -> Result<Self, NumberError> {
+        bounded(self + other)
+    }
+
+    fn checked_sub(&self, other: &Self) -> Result<Self, NumberError> {
+        bounded(self - other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Result<Self, NumberError> {
+        bounded(self * other)
+    }
+
+    fn checked_div(&self, other: &Self) -> Result<Self, NumberError> {
+        bounded(self / other)
+    }
+
+    fn checked_pow(&self, exponent: &Self) -> Result<Self, NumberError> {
+        if exponent.is_integer() {
+            let exponent = exponent.to_integer().to_i32().ok_or(NumberError::Overflow)?;
+            if bits(self).saturating_mul(u64::from(exponent.unsigned_abs())) > MAX_RATIONAL_BITS {
+                return Err(NumberError::Overflow);
+            }
+            return Ok(Pow::pow(self, exponent));
+        }
+        let root = exponent.denom().to_u32().and_then(|degree| exact_root(self, degree)).ok_or_else(|| {
+            let base = if Signed::is_negative(self) { format!("({})", self) } else { self.to_string() };
+            NumberError::Domain(format!("{}^({}) has no exact rational result", base, exponent))
+        })?;
+        root.checked_pow(&BigRational::from_integer(exponent.numer().clone()))
+    }
+
+    fn negate(&self) -> Self {
+        -self
+    }
+
+    fn apply_function(function: Function, arguments: &[Self], _angle_mode: AngleMode) -> Option<Result<Self, NumberError>> {
+        match function {
+            Function::Sqrt if !Signed::is_negative(&arguments[0]) => exact_root(&arguments[0], 2).map(Ok),
+            _ => None,
+        }
+    }
+
+    fn inexact(what: &str) -> Option<NumberError> {
+        Some(NumberError::Domain(format!("{} has no exact rational result", what)))
+    }
+
+    fn abs(&self) -> Self {
+        Signed::abs(self)
+    }
+
+    fn floor(&self) -> Self {
+        BigRational::floor(self)
+    }
+
+    fn ceil(&self) -> Self {
+        BigRational::ceil(self)
+    }
+
+    fn round(&self) -> Self {
+        BigRational::round(self)
+    }
+
+    fn is_integer(&self) -> bool {
+        BigRational::is_integer(self)
+    }
+}
+
+fn bits(value: &BigRational) -> u64 {
+    value.numer().bits().max(value.denom().bits())
+}
+
+fn exact_root(value: &BigRational, degree: u32) -> Option<BigRational> {
+    if Signed::is_negative(value) && degree.is_multiple_of(2) {
+        return None;
+    }
+    let root = |part: &BigInt| Some(part.nth_root(degree)).filter(|root| root.pow(degree) == *part);
+    Some(BigRational::new(root(value.numer())?, root(value.denom())?))
+}
+
+fn bounded(value: BigRational) -> Result<BigRational, NumberError> {
+    if bits(&value) > MAX_RATIONAL_BITS {
+        Err(NumberError::Overflow)
+    }
+    else {
+        Ok(value)
+    }
+}
+
+pub(crate) fn format_float(value: f64) -> String {
+    let rounded = format!("{:.*e}", SIGNIFICANT_DIGITS - 1, value)
+        .parse::<f64>()
+        .unwrap_or(value);
+    if rounded != 0.0 && (rounded.abs() >= 1e16 || rounded.abs() < 1e-9) {
+        format!("{:e}", rounded)
+    }
+    else {
+        format!("{}", rounded)
+    }
+}
+
+/// The number type expressions are evaluated in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// 64-bit floating point.
+    #[default]
+    Float,
+    /// Decimal floating point with 28 significant digits.
+    Decimal,
+    /// Exact fractions of any size.
+    Rational,
+    /// Complex numbers with f64 parts.
+    Complex,
+    /// Fixed-width integers with bitwise operators, see [`WordSize`](crate::WordSize).
+    Programmer,
+}
+
+impl Backend {
+    /// Every backend, in display order.
+    pub const ALL: [Backend; 5] = [Backend::Float, Backend::Decimal, Backend::Rational, Backend::Complex, Backend::Programmer];
+
+    /// Returns a short name for buttons and the command line.
+    pub fn label(self) -> &'static str {
+        match self {
+            Backend::Float => "f64",
+            Backend::Decimal => "decimal",
+            Backend::Rational => "rational",
+            Backend::Complex => "complex",
+            Backend::Programmer => "integer",
+        }
+    }
+}
+
+/// A result, in the representation of the backend it was computed in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// A result of the f64 backend.
+    Float(f64),
+    /// A result of the decimal backend.
+    Decimal(Decimal),
+    /// A result of the rational backend.
+    Rational(BigRational),
+    /// A result of the programmer backend.
+    Integer {
+        /// The value, wrapped to `size`.
+        value: i128,
+        /// The word size it was computed with.
+        size: WordSize,
+    },
+    /// A result of the complex backend.
+    Complex(Complex),
+    /// A number with a unit.
+    Quantity(Box<QuantityValue>),
+    /// A matrix of scalar values.
+    Matrix {
+        /// Number of rows.
+        rows: usize,
+        /// Number of columns.
+        columns: usize,
+        /// The `rows * columns` elements in row-major order.
+        elements: Vec<Value>,
+    },
+    /// A symbolic result, such as the output of `simplify`.
+    Expression(String),
+}
+
+impl Value {
+    /// Converts to f64. Quantities give their value in base units. Matrices, symbolic results
+    /// and complex numbers with an imaginary part give NaN.
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Value::Float(value) => *value,
+            Value::Decimal(value) => Number::to_f64(value),
+            Value::Rational(value) => Number::to_f64(value),
+            Value::Integer { value, .. } => *value as f64,
+            Value::Complex(value) => if value.is_real() { value.re } else { f64::NAN },
+            Value::Quantity(quantity) => quantity.base.to_f64(),
+            Value::Matrix { .. } | Value::Expression(_) => f64::NAN,
+        }
+    }
+
+    /// Formats the value for display.
+    pub fn format(&self, options: &DisplayOptions) -> String {
+        match self {
+            Value::Float(value) => format_float(*value),
+            Value::Decimal(value) => value.normalize().to_string(),
+            Value::Rational(value) => match options.rational {
+                RationalDisplay::Fraction => value.to_string(),
+                RationalDisplay::Decimal => format_rational(value, options.decimal_digits),
+            },
+            Value::Integer { value, .. } => value.to_string(),
+            Value::Complex(value) => value.format(options.complex),
+            Value::Quantity(quantity) => format!("{} {}", quantity.magnitude.format(options), quantity.unit),
+            Value::Matrix { columns, elements, .. } => {
+                let rows = elements.chunks(*columns)
+                    .map(|row| row.iter().map(|element| element.format(options)).collect::<Vec<_>>().join(", "))
+                    .collect::<Vec<_>>();
+                format!("[{}]", rows.join("; "))
+            }
+            Value::Expression(expression) => expression.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.format(&DisplayOptions::default()))
+    }
+}
+
+/// How exact rationals are formatted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RationalDisplay {
+    /// As `a/b`.
+    #[default]
+    Fraction,
+    /// As a decimal rounded to [`DisplayOptions::decimal_digits`].
+    Decimal,
+}
+
+/// Formatting options for [`Value::format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayOptions {
+    /// How rationals are shown.
+    pub rational: RationalDisplay,
+    /// Digits after the decimal point when rationals are shown as decimals.
+    pub decimal_digits: usize,
+    /// How complex numbers are shown.
+    pub complex: ComplexDisplay,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        DisplayOptions { rational: RationalDisplay::Fraction, decimal_digits: 20, complex: ComplexDisplay::Rectangular }
+    }
+}
+
+fn format_rational(value: &BigRational, digits: usize) -> String {
+    let scale = BigInt::from(10).pow(digits as u32);
+    let scaled = (Signed::abs(value) * BigRational::from_integer(scale)).round().to_integer();
+    let mut text = format!("{:0>width$}", scaled.to_string(), width = digits + 1);
+    text.insert(text.len() - digits, '.');
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if Signed::is_negative(value) && text.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        format!("-{}", text)
+    }
+    else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Backend, CalcError, Calculator};
+
+    #[test]
+    fn keeps_rational_roots_exact() {
+        let mut calculator = Calculator::default();
+        calculator.set_backend(Backend::Rational);
+        for (input, expected) in [("sqrt(9/4)", "3/2"), ("(27/8)^(2/3)", "9/4"), ("(-8)^(1/3)", "-2"), ("4^-0.5", "1/2"), ("stdev(2, 4, 6)", "2")] {
+            let value = calculator.evaluate_expression(input).unwrap_or_else(|error| panic!("{} gave {}", input, error));
+            assert_eq!(value.to_string(), expected, "{}", input);
+        }
+        for input in ["2^0.5", "(-4)^0.5", "sqrt(2)", "sin(1)", "ln(2)", "log(2, 8)", "pi", "e^2", "2 * pi", "90°"] {
+            match calculator.evaluate_expression(input) {
+                Err(CalcError::Domain { message, .. }) => assert!(message.contains("no exact rational result"), "{}", message),
+                result => panic!("{} gave {:?}", input, result),
+            }
+        }
+    }
+}
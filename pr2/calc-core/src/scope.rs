@@ -0,0 +1,45 @@
+use super::expr::Expr;
+
+pub(crate) const MAX_CALL_DEPTH: usize = 100;
+
+/// A function defined with `f(x) = expression`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserFunction {
+    /// Parameter names in order.
+    pub parameters: Vec<String>,
+    pub(crate) body: Expr,
+    /// The definition as it was typed.
+    pub source: String,
+}
+
+pub(crate) struct Scope<'a, N> {
+    parameters: &'a [String],
+    arguments: Vec<N>,
+    depth: usize,
+    parent: Option<&'a Scope<'a, N>>,
+}
+
+impl<'a, N: Clone> Scope<'a, N> {
+    pub(crate) fn global() -> Self {
+        Scope { parameters: &[], arguments: Vec::new(), depth: 0, parent: None }
+    }
+
+    pub(crate) fn call<'b>(&self, function: &'b UserFunction, arguments: Vec<N>) -> Scope<'b, N> {
+        Scope { parameters: &function.parameters, arguments, depth: self.depth + 1, parent: None }
+    }
+
+    pub(crate) fn bind<'b>(&'b self, parameters: &'b [String], arguments: Vec<N>) -> Scope<'b, N> {
+        Scope { parameters, arguments, depth: self.depth, parent: Some(self) }
+    }
+
+    pub(crate) fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<N> {
+        self.parameters.iter()
+            .position(|parameter| parameter == name)
+            .map(|index| self.arguments[index].clone())
+            .or_else(|| self.parent.and_then(|parent| parent.get(name)))
+    }
+}
@@ -0,0 +1,135 @@
+use std::cmp::Ordering;
+
+use super::functions::{AngleMode, Function};
+use super::number::{Number, NumberError};
+
+fn domain(message: &str) -> NumberError {
+    NumberError::Domain(String::from(message))
+}
+
+fn count<N: Number>(values: &[N]) -> Result<N, NumberError> {
+    N::from_f64(values.len() as f64).ok_or(NumberError::Overflow)
+}
+
+fn sum<N: Number>(values: &[N]) -> Result<N, NumberError> {
+    values[1..].iter().try_fold(values[0].clone(), |total, value| total.checked_add(value))
+}
+
+pub(crate) fn sort<N: Number>(values: &mut [N]) -> Result<(), NumberError> {
+    for value in values.iter() {
+        value.check_compatible(&values[0])?;
+    }
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    Ok(())
+}
+
+fn sorted<N: Number>(values: &[N]) -> Result<Vec<N>, NumberError> {
+    let mut sorted = values.to_vec();
+    sort(&mut sorted)?;
+    Ok(sorted)
+}
+
+fn deviation_products<N: Number>(values: &[N], others: &[N]) -> Result<N, NumberError> {
+    let (mean, other_mean) = (mean(values)?, mean(others)?);
+    let mut total = N::zero();
+    for (value, other) in values.iter().zip(others) {
+        total = total.checked_add(&value.checked_sub(&mean)?.checked_mul(&other.checked_sub(&other_mean)?)?)?;
+    }
+    Ok(total)
+}
+
+pub(crate) fn mean<N: Number>(values: &[N]) -> Result<N, NumberError> {
+    sum(values)?.checked_div(&count(values)?)
+}
+
+pub(crate) fn median<N: Number>(values: &[N]) -> Result<N, NumberError> {
+    sorted_median(&sorted(values)?)
+}
+
+pub(crate) fn sorted_median<N: Number>(sorted: &[N]) -> Result<N, NumberError> {
+    let middle = sorted.len() / 2;
+    if sorted.len() % 2 == 1 {
+        Ok(sorted[middle].clone())
+    }
+    else {
+        mean(&sorted[middle - 1..=middle])
+    }
+}
+
+pub(crate) fn mode<N: Number>(values: &[N]) -> Result<N, NumberError> {
+    sorted_mode(&sorted(values)?)
+}
+
+pub(crate) fn sorted_mode<N: Number>(sorted: &[N]) -> Result<N, NumberError> {
+    let mut best = (&sorted[0], 0);
+    let mut start = 0;
+    for index in 1..=sorted.len() {
+        if index == sorted.len() || sorted[index] != sorted[start] {
+            if index - start > best.1 {
+                best = (&sorted[start], index - start);
+            }
+            start = index;
+        }
+    }
+    Ok(best.0.clone())
+}
+
+pub(crate) fn variance<N: Number>(values: &[N]) -> Result<N, NumberError> {
+    if values.len() < 2 {
+        return Err(domain("Variance needs at least two values"));
+    }
+    let degrees_of_freedom = N::from_f64((values.len() - 1) as f64).ok_or(NumberError::Overflow)?;
+    deviation_products(values, values)?.checked_div(&degrees_of_freedom)
+}
+
+pub(crate) fn percentile<N: Number>(values: &[N], percent: &N) -> Result<N, NumberError> {
+    sorted_percentile(&sorted(values)?, percent)
+}
+
+pub(crate) fn sorted_percentile<N: Number>(sorted: &[N], percent: &N) -> Result<N, NumberError> {
+    let hundred = N::from_f64(100.0).ok_or(NumberError::Overflow)?;
+    if percent.is_negative() || *percent > hundred {
+        return Err(domain("Percentile must be between 0 and 100"));
+    }
+    let last = N::from_f64((sorted.len() - 1) as f64).ok_or(NumberError::Overflow)?;
+    let rank = percent.checked_mul(&last)?.checked_div(&hundred)?;
+    let lower = rank.floor();
+    let index = lower.to_f64() as usize;
+    if index + 1 >= sorted.len() {
+        return Ok(sorted[sorted.len() - 1].clone());
+    }
+    let fraction = rank.checked_sub(&lower)?;
+    let step = sorted[index + 1].checked_sub(&sorted[index])?;
+    sorted[index].checked_add(&step.checked_mul(&fraction)?)
+}
+
+fn check_pairs<N: Number>(xs: &[N], ys: &[N]) -> Result<(), NumberError> {
+    if xs.len() != ys.len() {
+        return Err(NumberError::Dimension(format!("Cannot pair {} x values with {} y values", xs.len(), ys.len())));
+    }
+    if xs.len() < 2 {
+        return Err(domain("Regression needs at least two points"));
+    }
+    Ok(())
+}
+
+pub(crate) fn regression<N: Number>(xs: &[N], ys: &[N]) -> Result<(N, N), NumberError> {
+    check_pairs(xs, ys)?;
+    let sxx = deviation_products(xs, xs)?;
+    if sxx.is_zero() {
+        return Err(domain("Regression needs at least two distinct x values"));
+    }
+    let slope = deviation_products(xs, ys)?.checked_div(&sxx)?;
+    let intercept = mean(ys)?.checked_sub(&slope.checked_mul(&mean(xs)?)?)?;
+    Ok((slope, intercept))
+}
+
+pub(crate) fn correlation<N: Number>(xs: &[N], ys: &[N]) -> Result<N, NumberError> {
+    check_pairs(xs, ys)?;
+    let spread = deviation_products(xs, xs)?.checked_mul(&deviation_products(ys, ys)?)?;
+    if spread.is_zero() {
+        return Err(domain("Correlation is undefined for constant values"));
+    }
+    let root = Function::Sqrt.apply(&[spread], AngleMode::default())?;
+    deviation_products(xs, ys)?.checked_div(&root)
+}
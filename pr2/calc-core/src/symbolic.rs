@@ -0,0 +1,613 @@
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use super::functions::AngleMode;
+
+const MAX_REWRITES: usize = 10_000;
+const MAX_POWER: i32 = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Symbolic {
+    Number(BigRational),
+    Symbol(String),
+    Neg(Box<Symbolic>),
+    Add(Box<Symbolic>, Box<Symbolic>),
+    Sub(Box<Symbolic>, Box<Symbolic>),
+    Mul(Box<Symbolic>, Box<Symbolic>),
+    Div(Box<Symbolic>, Box<Symbolic>),
+    Pow(Box<Symbolic>, Box<Symbolic>),
+    Call(String, Vec<Symbolic>),
+}
+
+pub(crate) type Rule = fn(&Symbolic) -> Option<Symbolic>;
+
+pub(crate) const RULES: [Rule; 4] = [fold_constants, identities, collect_factors, collect_terms];
+
+impl Symbolic {
+    pub(crate) fn integer(value: i64) -> Symbolic {
+        Symbolic::Number(BigRational::from_integer(BigInt::from(value)))
+    }
+
+    pub(crate) fn symbol(name: &str) -> Symbolic {
+        Symbolic::Symbol(name.to_string())
+    }
+
+    pub(crate) fn call(name: &str, arguments: Vec<Symbolic>) -> Symbolic {
+        Symbolic::Call(name.to_string(), arguments)
+    }
+
+    pub(crate) fn pow(self, exponent: Symbolic) -> Symbolic {
+        Symbolic::Pow(Box::new(self), Box::new(exponent))
+    }
+
+    fn as_number(&self) -> Option<&BigRational> {
+        match self {
+            Symbolic::Number(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn is_number(&self, value: i64) -> bool {
+        self.as_number().is_some_and(|number| *number == BigRational::from_integer(BigInt::from(value)))
+    }
+
+    fn contains(&self, variable: &str) -> bool {
+        match self {
+            Symbolic::Number(_) => false,
+            Symbolic::Symbol(name) => name == variable,
+            Symbolic::Neg(operand) => operand.contains(variable),
+            Symbolic::Add(left, right)
+            | Symbolic::Sub(left, right)
+            | Symbolic::Mul(left, right)
+            | Symbolic::Div(left, right)
+            | Symbolic::Pow(left, right) => left.contains(variable) || right.contains(variable),
+            Symbolic::Call(_, arguments) => arguments.iter().any(|argument| argument.contains(variable)),
+        }
+    }
+
+    fn map_children(&self, mut f: impl FnMut(&Symbolic) -> Symbolic) -> Symbolic {
+        let mut binary = |left: &Symbolic, right: &Symbolic| (Box::new(f(left)), Box::new(f(right)));
+        match self {
+            Symbolic::Number(_) | Symbolic::Symbol(_) => self.clone(),
+            Symbolic::Neg(operand) => Symbolic::Neg(Box::new(f(operand))),
+            Symbolic::Add(left, right) => {
+                let (left, right) = binary(left, right);
+                Symbolic::Add(left, right)
+            }
+            Symbolic::Sub(left, right) => {
+                let (left, right) = binary(left, right);
+                Symbolic::Sub(left, right)
+            }
+            Symbolic::Mul(left, right) => {
+                let (left, right) = binary(left, right);
+                Symbolic::Mul(left, right)
+            }
+            Symbolic::Div(left, right) => {
+                let (left, right) = binary(left, right);
+                Symbolic::Div(left, right)
+            }
+            Symbolic::Pow(left, right) => {
+                let (left, right) = binary(left, right);
+                Symbolic::Pow(left, right)
+            }
+            Symbolic::Call(name, arguments) => Symbolic::Call(name.clone(), arguments.iter().map(f).collect()),
+        }
+    }
+
+    pub(crate) fn substitute(&self, bindings: &[(String, Symbolic)]) -> Symbolic {
+        match self {
+            Symbolic::Symbol(name) => bindings.iter()
+                .find(|(parameter, _)| parameter == name)
+                .map_or_else(|| self.clone(), |(_, value)| value.clone()),
+            _ => self.map_children(|child| child.substitute(bindings)),
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Symbolic::Add(..) | Symbolic::Sub(..) => 1,
+            Symbolic::Mul(..) | Symbolic::Div(..) => 2,
+            Symbolic::Number(value) if !value.is_integer() => 2,
+            Symbolic::Neg(_) => 3,
+            Symbolic::Number(value) if value.is_negative() => 3,
+            Symbolic::Pow(..) => 4,
+            _ => 5,
+        }
+    }
+
+    fn is_signed(&self) -> bool {
+        matches!(self, Symbolic::Neg(_)) || self.as_number().is_some_and(|value| value.is_negative())
+    }
+}
+
+impl Neg for Symbolic {
+    type Output = Symbolic;
+
+    fn neg(self) -> Symbolic {
+        Symbolic::Neg(Box::new(self))
+    }
+}
+
+macro_rules! binary_operator {
+    ($trait:ident, $method:ident, $variant:ident) => {
+        impl $trait for Symbolic {
+            type Output = Symbolic;
+
+            fn $method(self, other: Symbolic) -> Symbolic {
+                Symbolic::$variant(Box::new(self), Box::new(other))
+            }
+        }
+    };
+}
+
+binary_operator!(Add, add, Add);
+binary_operator!(Sub, sub, Sub);
+binary_operator!(Mul, mul, Mul);
+binary_operator!(Div, div, Div);
+
+impl fmt::Display for Symbolic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (operator, left, right) = match self {
+            Symbolic::Number(value) if value.is_integer() => return write!(f, "{}", value.numer()),
+            Symbolic::Number(value) => return write!(f, "{}/{}", value.numer(), value.denom()),
+            Symbolic::Symbol(name) => return write!(f, "{}", name),
+            Symbolic::Call(name, arguments) => {
+                let arguments = arguments.iter().map(|argument| argument.to_string()).collect::<Vec<_>>();
+                return write!(f, "{}({})", name, arguments.join(", "));
+            }
+            Symbolic::Neg(operand) => {
+                let parenthesize = operand.precedence() < 2 || operand.is_signed();
+                return write!(f, "-{}", parenthesized(operand, parenthesize));
+            }
+            Symbolic::Add(left, right) => (" + ", left, right),
+            Symbolic::Sub(left, right) => (" - ", left, right),
+            Symbolic::Mul(left, right) => ("*", left, right),
+            Symbolic::Div(left, right) => ("/", left, right),
+            Symbolic::Pow(left, right) => ("^", left, right),
+        };
+        let precedence = self.precedence();
+        let power = matches!(self, Symbolic::Pow(..));
+        let left_parentheses = left.precedence() < precedence || (power && left.precedence() <= precedence);
+        let right_parentheses = right.precedence() < precedence
+            || (right.precedence() == precedence && matches!(self, Symbolic::Sub(..) | Symbolic::Div(..)))
+            || right.is_signed();
+        write!(f, "{}{}{}", parenthesized(left, left_parentheses), operator, parenthesized(right, right_parentheses))
+    }
+}
+
+fn parenthesized(expr: &Symbolic, parenthesize: bool) -> String {
+    if parenthesize { format!("({})", expr) } else { expr.to_string() }
+}
+
+pub(crate) fn simplify(expr: &Symbolic) -> Symbolic {
+    rewrite(expr, &RULES)
+}
+
+pub(crate) fn rewrite(expr: &Symbolic, rules: &[Rule]) -> Symbolic {
+    let mut budget = MAX_REWRITES;
+    rewrite_node(expr, rules, &mut budget)
+}
+
+fn rewrite_node(expr: &Symbolic, rules: &[Rule], budget: &mut usize) -> Symbolic {
+    let mut node = expr.map_children(|child| rewrite_node(child, rules, budget));
+    while *budget > 0 {
+        let Some(next) = rules.iter().find_map(|rule| rule(&node)) else {
+            break;
+        };
+        *budget -= 1;
+        node = next.map_children(|child| rewrite_node(child, rules, budget));
+    }
+    node
+}
+
+fn fold_constants(expr: &Symbolic) -> Option<Symbolic> {
+    let value = match expr {
+        Symbolic::Neg(operand) => -operand.as_number()?,
+        Symbolic::Add(left, right) => left.as_number()? + right.as_number()?,
+        Symbolic::Sub(left, right) => left.as_number()? - right.as_number()?,
+        Symbolic::Mul(left, right) => left.as_number()? * right.as_number()?,
+        Symbolic::Div(left, right) => {
+            let divisor = right.as_number()?;
+            if divisor.is_zero() {
+                return None;
+            }
+            left.as_number()? / divisor
+        }
+        Symbolic::Pow(base, exponent) => power(base.as_number()?, exponent.as_number()?)?,
+        Symbolic::Call(name, arguments) => match (name.as_str(), arguments.as_slice()) {
+            ("sin" | "tan" | "asin" | "atan" | "sqrt", [argument]) if argument.is_number(0) => BigRational::zero(),
+            ("cos", [argument]) if argument.is_number(0) => BigRational::one(),
+            ("ln", [argument]) if argument.is_number(1) => BigRational::zero(),
+            ("ln", [Symbolic::Symbol(name)]) if name == "e" => BigRational::one(),
+            ("abs", [Symbolic::Number(value)]) => value.abs(),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(Symbolic::Number(value))
+}
+
+fn power(base: &BigRational, exponent: &BigRational) -> Option<BigRational> {
+    let exponent = exponent.is_integer().then(|| exponent.to_integer().to_i32()).flatten()?;
+    if exponent.abs() > MAX_POWER || (base.is_zero() && exponent < 0) {
+        return None;
+    }
+    Some(num_traits::Pow::pow(base, exponent))
+}
+
+fn identities(expr: &Symbolic) -> Option<Symbolic> {
+    match expr {
+        Symbolic::Neg(operand) => match &**operand {
+            Symbolic::Neg(inner) => Some((**inner).clone()),
+            _ => None,
+        },
+        Symbolic::Add(left, right) if left.is_number(0) => Some((**right).clone()),
+        Symbolic::Add(left, right) if right.is_number(0) => Some((**left).clone()),
+        Symbolic::Add(left, right) => match &**right {
+            Symbolic::Neg(inner) => Some((**left).clone() - (**inner).clone()),
+            _ => None,
+        },
+        Symbolic::Sub(left, right) if right.is_number(0) => Some((**left).clone()),
+        Symbolic::Sub(left, right) if left.is_number(0) => Some(-(**right).clone()),
+        Symbolic::Sub(left, right) => match &**right {
+            Symbolic::Neg(inner) => Some((**left).clone() + (**inner).clone()),
+            _ => None,
+        },
+        Symbolic::Mul(left, right) if left.is_number(0) || right.is_number(0) => Some(Symbolic::integer(0)),
+        Symbolic::Mul(left, right) if left.is_number(1) => Some((**right).clone()),
+        Symbolic::Mul(left, right) if right.is_number(1) => Some((**left).clone()),
+        Symbolic::Div(left, right) if left.is_number(0) && !right.is_number(0) => Some(Symbolic::integer(0)),
+        Symbolic::Div(left, right) if right.is_number(1) => Some((**left).clone()),
+        Symbolic::Pow(_, exponent) if exponent.is_number(0) => Some(Symbolic::integer(1)),
+        Symbolic::Pow(base, exponent) if exponent.is_number(1) => Some((**base).clone()),
+        Symbolic::Pow(base, _) if base.is_number(1) => Some(Symbolic::integer(1)),
+        Symbolic::Pow(base, outer) => match &**base {
+            Symbolic::Pow(inner_base, inner) if outer.as_number().is_some_and(|value| value.is_integer()) => {
+                Some((**inner_base).clone().pow((**inner).clone() * (**outer).clone()))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+type Factors = Vec<(Symbolic, Symbolic)>;
+
+fn flatten_product(expr: &Symbolic, inverted: bool, coefficient: &mut BigRational, factors: &mut Factors) {
+    match expr {
+        Symbolic::Number(value) if !value.is_zero() || !inverted => {
+            if inverted { *coefficient /= value } else { *coefficient *= value }
+        }
+        Symbolic::Neg(operand) => {
+            *coefficient = -coefficient.clone();
+            flatten_product(operand, inverted, coefficient, factors);
+        }
+        Symbolic::Mul(left, right) => {
+            flatten_product(left, inverted, coefficient, factors);
+            flatten_product(right, inverted, coefficient, factors);
+        }
+        Symbolic::Div(left, right) => {
+            flatten_product(left, inverted, coefficient, factors);
+            flatten_product(right, !inverted, coefficient, factors);
+        }
+        _ => {
+            let (base, exponent) = match expr {
+                Symbolic::Pow(base, exponent) => ((**base).clone(), (**exponent).clone()),
+                _ => (expr.clone(), Symbolic::integer(1)),
+            };
+            let exponent = if inverted { negated(exponent) } else { exponent };
+            match factors.iter_mut().find(|(existing, _)| *existing == base) {
+                Some((_, existing)) => *existing = added(existing.clone(), exponent),
+                None => factors.push((base, exponent)),
+            }
+        }
+    }
+}
+
+fn negated(expr: Symbolic) -> Symbolic {
+    match expr {
+        Symbolic::Number(value) => Symbolic::Number(-value),
+        expr => -expr,
+    }
+}
+
+fn added(left: Symbolic, right: Symbolic) -> Symbolic {
+    match (left.as_number(), right.as_number()) {
+        (Some(left), Some(right)) => Symbolic::Number(left + right),
+        _ => left + right,
+    }
+}
+
+fn build_product(coefficient: &BigRational, factors: &[(Symbolic, Symbolic)]) -> Symbolic {
+    if coefficient.is_zero() {
+        return Symbolic::integer(0);
+    }
+    let magnitude = coefficient.abs();
+    let mut numerator = Vec::new();
+    let mut denominator = Vec::new();
+    if !magnitude.numer().is_one() {
+        numerator.push(Symbolic::Number(BigRational::from_integer(magnitude.numer().clone())));
+    }
+    if !magnitude.denom().is_one() {
+        denominator.push(Symbolic::Number(BigRational::from_integer(magnitude.denom().clone())));
+    }
+    for (base, exponent) in factors {
+        if exponent.is_number(0) {
+            continue;
+        }
+        match exponent.as_number() {
+            Some(value) if value.is_negative() => denominator.push(powered(base, Symbolic::Number(-value))),
+            _ => numerator.push(powered(base, exponent.clone())),
+        }
+    }
+    let numerator = numerator.into_iter().reduce(Mul::mul).unwrap_or_else(|| Symbolic::integer(1));
+    let product = match denominator.into_iter().reduce(Mul::mul) {
+        Some(denominator) => numerator / denominator,
+        None => numerator,
+    };
+    if coefficient.is_negative() { negated(product) } else { product }
+}
+
+fn powered(base: &Symbolic, exponent: Symbolic) -> Symbolic {
+    if exponent.is_number(1) { base.clone() } else { base.clone().pow(exponent) }
+}
+
+fn collect_factors(expr: &Symbolic) -> Option<Symbolic> {
+    if !matches!(expr, Symbolic::Mul(..) | Symbolic::Div(..)) {
+        return None;
+    }
+    let mut coefficient = BigRational::one();
+    let mut factors = Factors::new();
+    flatten_product(expr, false, &mut coefficient, &mut factors);
+    let product = build_product(&coefficient, &factors);
+    (product != *expr).then_some(product)
+}
+
+fn flatten_sum(expr: &Symbolic, negative: bool, terms: &mut Vec<(BigRational, Factors)>) {
+    match expr {
+        Symbolic::Add(left, right) => {
+            flatten_sum(left, negative, terms);
+            flatten_sum(right, negative, terms);
+        }
+        Symbolic::Sub(left, right) => {
+            flatten_sum(left, negative, terms);
+            flatten_sum(right, !negative, terms);
+        }
+        Symbolic::Neg(operand) if matches!(**operand, Symbolic::Add(..) | Symbolic::Sub(..)) => {
+            flatten_sum(operand, !negative, terms);
+        }
+        _ => {
+            let mut coefficient = BigRational::one();
+            let mut factors = Factors::new();
+            flatten_product(expr, false, &mut coefficient, &mut factors);
+            if negative {
+                coefficient = -coefficient;
+            }
+            match terms.iter_mut().find(|(_, existing)| *existing == factors) {
+                Some((existing, _)) => *existing += coefficient,
+                None => terms.push((coefficient, factors)),
+            }
+        }
+    }
+}
+
+fn collect_terms(expr: &Symbolic) -> Option<Symbolic> {
+    if !matches!(expr, Symbolic::Add(..) | Symbolic::Sub(..)) {
+        return None;
+    }
+    let mut terms = Vec::new();
+    flatten_sum(expr, false, &mut terms);
+    let mut sum: Option<Symbolic> = None;
+    for (coefficient, factors) in terms.iter().filter(|(coefficient, _)| !coefficient.is_zero()) {
+        sum = Some(match sum {
+            None => build_product(coefficient, factors),
+            Some(sum) if coefficient.is_negative() => sum - build_product(&-coefficient, factors),
+            Some(sum) => sum + build_product(coefficient, factors),
+        });
+    }
+    let sum = sum.unwrap_or_else(|| Symbolic::integer(0));
+    (sum != *expr).then_some(sum)
+}
+
+pub(crate) fn derivative(expr: &Symbolic, variable: &str, angle_mode: AngleMode) -> Result<Symbolic, String> {
+    let d = |expr: &Symbolic| derivative(expr, variable, angle_mode);
+    Ok(match expr {
+        Symbolic::Number(_) => Symbolic::integer(0),
+        Symbolic::Symbol(name) => Symbolic::integer(i64::from(name == variable)),
+        Symbolic::Neg(operand) => -d(operand)?,
+        Symbolic::Add(left, right) => d(left)? + d(right)?,
+        Symbolic::Sub(left, right) => d(left)? - d(right)?,
+        Symbolic::Mul(left, right) => d(left)? * (**right).clone() + (**left).clone() * d(right)?,
+        Symbolic::Div(left, right) => {
+            let numerator = d(left)? * (**right).clone() - (**left).clone() * d(right)?;
+            numerator / (**right).clone().pow(Symbolic::integer(2))
+        }
+        Symbolic::Pow(base, exponent) => {
+            let (base, exponent) = ((**base).clone(), (**exponent).clone());
+            if !exponent.contains(variable) {
+                exponent.clone() * base.clone().pow(exponent - Symbolic::integer(1)) * d(&base)?
+            }
+            else if !base.contains(variable) {
+                base.clone().pow(exponent.clone()) * Symbolic::call("ln", vec![base]) * d(&exponent)?
+            }
+            else {
+                let inner = d(&exponent)? * Symbolic::call("ln", vec![base.clone()]) + exponent.clone() * d(&base)? / base.clone();
+                base.pow(exponent) * inner
+            }
+        }
+        Symbolic::Call(name, arguments) => {
+            if let ("log", [base, value]) = (name.as_str(), arguments.as_slice()) {
+                let ln = |argument: &Symbolic| Symbolic::call("ln", vec![argument.clone()]);
+                return d(&(ln(value) / ln(base)));
+            }
+            let [argument] = arguments.as_slice() else {
+                return Err(format!("Cannot differentiate {}", name));
+            };
+            let u = argument.clone();
+            let one = || Symbolic::integer(1);
+            let degrees = angle_mode == AngleMode::Degrees;
+            let to_radians = || Symbolic::symbol("pi") / Symbolic::integer(180);
+            let outer = match name.as_str() {
+                "sin" => Symbolic::call("cos", vec![u]),
+                "cos" => -Symbolic::call("sin", vec![u]),
+                "tan" => one() / Symbolic::call("cos", vec![u]).pow(Symbolic::integer(2)),
+                "asin" => one() / Symbolic::call("sqrt", vec![one() - u.pow(Symbolic::integer(2))]),
+                "acos" => -(one() / Symbolic::call("sqrt", vec![one() - u.pow(Symbolic::integer(2))])),
+                "atan" => one() / (one() + u.pow(Symbolic::integer(2))),
+                "sqrt" => one() / (Symbolic::integer(2) * Symbolic::call("sqrt", vec![u])),
+                "ln" => one() / u,
+                "log10" => one() / (u * Symbolic::call("ln", vec![Symbolic::integer(10)])),
+                "abs" => u.clone() / Symbolic::call("abs", vec![u]),
+                _ => return Err(format!("Cannot differentiate {}", name)),
+            };
+            let outer = match name.as_str() {
+                "sin" | "cos" | "tan" if degrees => outer * to_radians(),
+                "asin" | "acos" | "atan" if degrees => outer / to_radians(),
+                _ => outer,
+            };
+            outer * d(argument)?
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Calculator, Value};
+
+    fn evaluated(calculator: &mut Calculator, input: &str) -> String {
+        match calculator.calculate(input) {
+            Ok(Some(Value::Expression(expression))) => expression,
+            other => panic!("{} gave {:?}", input, other),
+        }
+    }
+
+    fn simplified(input: &str) -> String {
+        evaluated(&mut Calculator::default(), &format!("simplify({})", input))
+    }
+
+    fn derived(input: &str) -> String {
+        evaluated(&mut Calculator::default(), &format!("d/dx ({})", input))
+    }
+
+    fn x() -> Symbolic {
+        Symbolic::symbol("x")
+    }
+
+    #[test]
+    fn collects_like_terms() {
+        assert_eq!(simplified("2*x + 3*x"), "5*x");
+        assert_eq!(simplified("x + x"), "2*x");
+        assert_eq!(simplified("x - x"), "0");
+        assert_eq!(simplified("2*x + 3*y - x"), "x + 3*y");
+        assert_eq!(simplified("-(x - y) + x"), "y");
+    }
+
+    #[test]
+    fn collects_factors_and_powers() {
+        assert_eq!(simplified("x*x"), "x^2");
+        assert_eq!(simplified("x^2*x^3"), "x^5");
+        assert_eq!(simplified("x/x"), "1");
+        assert_eq!(simplified("2*(3*x)"), "6*x");
+        assert_eq!(simplified("(x^2)^3"), "x^6");
+        assert_eq!(simplified("x*y/x"), "y");
+        assert_eq!(simplified("x/2 + x/2"), "x");
+    }
+
+    #[test]
+    fn folds_constants_exactly() {
+        assert_eq!(simplified("1/2 + 1/3"), "5/6");
+        assert_eq!(simplified("2^10"), "1024");
+        assert_eq!(simplified("0*sin(x) + 1*y"), "y");
+        assert_eq!(simplified("ln(e) + cos(0)"), "2");
+    }
+
+    #[test]
+    fn differentiates_exactly() {
+        assert_eq!(derived("x^3 * sin(x)"), "3*x^2*sin(x) + x^3*cos(x)");
+        assert_eq!(derived("5"), "0");
+        assert_eq!(derived("ln(x)"), "1/x");
+        assert_eq!(derived("e^x"), "e^x");
+        assert_eq!(derived("sin(x)^2"), "2*sin(x)*cos(x)");
+        assert_eq!(derived("1/x"), "-1/x^2");
+        assert_eq!(derived("x^x"), "x^x*(ln(x) + 1)");
+    }
+
+    #[test]
+    fn differentiates_with_respect_to_the_named_variable() {
+        let mut calculator = Calculator::default();
+        assert_eq!(evaluated(&mut calculator, "d/dt (t^2 * x)"), "2*t*x");
+        calculator.calculate("f(a) = a^2 + a").unwrap();
+        assert_eq!(evaluated(&mut calculator, "d/dx (f(x))"), "2*x + 1");
+    }
+
+    #[test]
+    fn respects_the_angle_mode() {
+        let mut calculator = Calculator::default();
+        calculator.set_angle_mode(AngleMode::Degrees);
+        assert_eq!(evaluated(&mut calculator, "d/dx (sin(x))"), "cos(x)*pi/180");
+    }
+
+    #[test]
+    fn reports_functions_without_a_derivative() {
+        let error = Calculator::default().calculate("d/dx (floor(x))").unwrap_err();
+        assert_eq!(error.to_string(), "Cannot differentiate floor at position 7");
+    }
+
+    #[test]
+    fn prints_minimal_parentheses() {
+        let (a, b, c) = (Symbolic::symbol("a"), Symbolic::symbol("b"), Symbolic::symbol("c"));
+        assert_eq!(((a.clone() + b.clone()) * c.clone()).to_string(), "(a + b)*c");
+        assert_eq!((a.clone() + b.clone() * c.clone()).to_string(), "a + b*c");
+        assert_eq!((a.clone() - (b.clone() - c.clone())).to_string(), "a - (b - c)");
+        assert_eq!((a.clone() - b.clone() - c.clone()).to_string(), "a - b - c");
+        assert_eq!((a.clone() / (b.clone() * c.clone())).to_string(), "a/(b*c)");
+        assert_eq!(a.clone().pow(b.clone().pow(c.clone())).to_string(), "a^b^c");
+        assert_eq!(a.clone().pow(b.clone()).pow(c.clone()).to_string(), "(a^b)^c");
+        assert_eq!((-a.clone()).pow(Symbolic::integer(2)).to_string(), "(-a)^2");
+        assert_eq!((a.clone() * -b.clone()).to_string(), "a*(-b)");
+        assert_eq!((-(a.clone() * b.clone())).to_string(), "-a*b");
+        assert_eq!((-(a + b)).to_string(), "-(a + b)");
+    }
+
+    #[test]
+    fn rewrites_bottom_up_with_custom_rules() {
+        fn double_to_sum(expr: &Symbolic) -> Option<Symbolic> {
+            match expr {
+                Symbolic::Mul(left, right) if left.is_number(2) => Some((**right).clone() + (**right).clone()),
+                _ => None,
+            }
+        }
+        let expr = Symbolic::call("sin", vec![Symbolic::integer(2) * x()]);
+        assert_eq!(rewrite(&expr, &[double_to_sum]).to_string(), "sin(x + x)");
+        assert_eq!(rewrite(&expr, &[]), expr);
+    }
+
+    #[test]
+    fn rules_apply_individually() {
+        assert_eq!(fold_constants(&(Symbolic::integer(2) + Symbolic::integer(3))), Some(Symbolic::integer(5)));
+        assert_eq!(fold_constants(&(x() + Symbolic::integer(3))), None);
+        assert_eq!(identities(&(x() * Symbolic::integer(1))), Some(x()));
+        assert_eq!(identities(&(x() - -x())), Some(x() + x()));
+        assert_eq!(collect_terms(&(x() + x())), Some(Symbolic::integer(2) * x()));
+        assert_eq!(collect_factors(&(x() * x())), Some(x().pow(Symbolic::integer(2))));
+        assert_eq!(collect_factors(&(Symbolic::integer(2) * x())), None);
+    }
+
+    #[test]
+    fn terminates_on_rules_that_never_settle() {
+        fn flip(expr: &Symbolic) -> Option<Symbolic> {
+            match expr {
+                Symbolic::Add(left, right) => Some((**right).clone() + (**left).clone()),
+                _ => None,
+            }
+        }
+        let expr = x() + Symbolic::symbol("y");
+        let result = rewrite(&expr, &[flip]);
+        assert!(result == expr || result == Symbolic::symbol("y") + x());
+    }
+}
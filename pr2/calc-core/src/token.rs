@@ -0,0 +1,169 @@
+/// A byte range of the input, used to point at errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    /// Offset of the first byte.
+    pub start: usize,
+    /// Offset just past the last byte.
+    pub end: usize,
+}
+
+impl Span {
+    pub(crate) fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    pub(crate) fn to(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+
+    pub(crate) fn shifted(self, offset: usize) -> Span {
+        Span::new(self.start + offset, self.end + offset)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    ElementMul,
+    ElementDiv,
+    Pow,
+    Bitwise(BitwiseOperator),
+    Polar,
+    Convert,
+    Equation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BitwiseOperator {
+    And,
+    Or,
+    Xor,
+    ShiftLeft,
+    ShiftRight,
+}
+
+impl Operator {
+    pub(crate) fn from_char(char: char) -> Option<Operator> {
+        match char {
+            '+' => Some(Operator::Add),
+            '-' => Some(Operator::Sub),
+            '*' => Some(Operator::Mul),
+            '/' => Some(Operator::Div),
+            '^' => Some(Operator::Pow),
+            '&' => Some(Operator::Bitwise(BitwiseOperator::And)),
+            '|' => Some(Operator::Bitwise(BitwiseOperator::Or)),
+            '∠' => Some(Operator::Polar),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn from_symbol(symbol: &str) -> Option<Operator> {
+        match symbol {
+            ".*" => Some(Operator::ElementMul),
+            "./" => Some(Operator::ElementDiv),
+            "<<" => Some(Operator::Bitwise(BitwiseOperator::ShiftLeft)),
+            ">>" => Some(Operator::Bitwise(BitwiseOperator::ShiftRight)),
+            "xor" => Some(Operator::Bitwise(BitwiseOperator::Xor)),
+            _ => {
+                let mut chars = symbol.chars();
+                chars.next().filter(|_| chars.next().is_none()).and_then(Operator::from_char)
+            }
+        }
+    }
+
+    pub(crate) fn symbol(self) -> &'static str {
+        match self {
+            Operator::Add => "+",
+            Operator::Sub => "-",
+            Operator::Mul => "*",
+            Operator::Div => "/",
+            Operator::ElementMul => ".*",
+            Operator::ElementDiv => "./",
+            Operator::Pow => "^",
+            Operator::Bitwise(BitwiseOperator::And) => "&",
+            Operator::Bitwise(BitwiseOperator::Or) => "|",
+            Operator::Bitwise(BitwiseOperator::Xor) => "xor",
+            Operator::Bitwise(BitwiseOperator::ShiftLeft) => "<<",
+            Operator::Bitwise(BitwiseOperator::ShiftRight) => ">>",
+            Operator::Polar => "∠",
+            Operator::Convert => "in",
+            Operator::Equation => "=",
+        }
+    }
+
+    pub(crate) fn priority(self) -> u8 {
+        match self {
+            Operator::Pow => 10,
+            Operator::Polar => 8,
+            Operator::Mul | Operator::Div | Operator::ElementMul | Operator::ElementDiv => 7,
+            Operator::Add | Operator::Sub => 6,
+            Operator::Bitwise(BitwiseOperator::ShiftLeft | BitwiseOperator::ShiftRight) => 5,
+            Operator::Bitwise(BitwiseOperator::And) => 4,
+            Operator::Bitwise(BitwiseOperator::Xor) => 3,
+            Operator::Bitwise(BitwiseOperator::Or) => 2,
+            Operator::Convert => 1,
+            Operator::Equation => 0,
+        }
+    }
+
+    pub(crate) fn is_right_associative(self) -> bool {
+        self == Operator::Pow
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UnaryOperator {
+    Plus,
+    Minus,
+    Not,
+    Degree,
+    Percent,
+}
+
+impl UnaryOperator {
+    pub(crate) fn symbol(self) -> &'static str {
+        match self {
+            UnaryOperator::Plus => "pos",
+            UnaryOperator::Minus => "neg",
+            UnaryOperator::Not => "~",
+            UnaryOperator::Degree => "°",
+            UnaryOperator::Percent => "%",
+        }
+    }
+
+    pub(crate) fn priority(self) -> u8 {
+        9
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TokenKind {
+    Number(String),
+    Operator(Operator),
+    UnaryOperator(UnaryOperator),
+    Factorial,
+    Degree,
+    Percent,
+    Identifier(String),
+    Function { name: String, arguments: usize },
+    Matrix { rows: Vec<usize> },
+    Comma,
+    Assign,
+    LeftParenthesis,
+    RightParenthesis,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Token {
+    pub(crate) kind: TokenKind,
+    pub(crate) span: Span,
+}
+
+impl Token {
+    pub(crate) fn new(kind: TokenKind, span: Span) -> Self {
+        Token { kind, span }
+    }
+}
@@ -0,0 +1,86 @@
+use super::number::{DisplayOptions, Value};
+use super::token::{Token, TokenKind};
+
+/// One operation of an evaluation trace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceStep {
+    /// The evaluated operands.
+    pub operands: Vec<Value>,
+    /// The operator or function that was applied.
+    pub operation: String,
+    /// The value it produced.
+    pub result: Value,
+}
+
+impl TraceStep {
+    /// Formats the step in postfix order, for example `2 3 * → 6`.
+    pub fn format(&self, options: &DisplayOptions) -> String {
+        let mut parts = self.operands.iter()
+            .map(|operand| {
+                let text = operand.format(options);
+                if text.contains(' ') { format!("({})", text) } else { text }
+            })
+            .collect::<Vec<_>>();
+        parts.push(self.operation.clone());
+        format!("{} → {}", parts.join(" "), self.result.format(options))
+    }
+}
+
+/// How an expression was evaluated, from [`Calculator::trace`](crate::Calculator::trace).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace {
+    /// The input split into tokens.
+    pub tokens: Vec<String>,
+    /// The tokens in postfix order.
+    pub postfix: Vec<String>,
+    /// Every operation in evaluation order.
+    pub steps: Vec<TraceStep>,
+    /// The final value, or `None` for function definitions.
+    pub result: Option<Value>,
+}
+
+pub(crate) fn token_text(token: &Token, expression: &str) -> Option<String> {
+    match &token.kind {
+        TokenKind::Matrix { .. } => None,
+        TokenKind::Function { name, .. } => Some(name.clone()),
+        TokenKind::Operator(operator) if token.span.start == token.span.end => Some(operator.symbol().to_string()),
+        _ => expression.get(token.span.start..token.span.end).map(str::to_string),
+    }
+}
+
+pub(crate) fn postfix_text(token: &Token) -> String {
+    match &token.kind {
+        TokenKind::Number(literal) => literal.clone(),
+        TokenKind::Identifier(name) | TokenKind::Function { name, .. } => name.clone(),
+        TokenKind::Operator(operator) => operator.symbol().to_string(),
+        TokenKind::UnaryOperator(operator) => operator.symbol().to_string(),
+        TokenKind::Factorial => String::from("!"),
+        TokenKind::Degree => String::from("°"),
+        TokenKind::Percent => String::from("%"),
+        TokenKind::Matrix { rows } => format!("[{}×{}]", rows.len(), rows[0]),
+        TokenKind::Comma => String::from(","),
+        TokenKind::Assign => String::from("="),
+        TokenKind::LeftParenthesis => String::from("("),
+        TokenKind::RightParenthesis => String::from(")"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Calculator, DisplayOptions, Value};
+
+    #[test]
+    fn calculates_and_traces_in_one_pass() {
+        let mut calculator = Calculator::default();
+        let trace = calculator.calculate_traced("x = (1 + 2) * 4").unwrap();
+        let steps = trace.steps.iter().map(|step| step.format(&DisplayOptions::default())).collect::<Vec<_>>();
+        assert_eq!(steps, ["1 2 + → 3", "3 4 * → 12"]);
+        assert_eq!(trace.result, Some(Value::Float(12.0)));
+        assert_eq!(calculator.variables().get("x"), Some(&Value::Float(12.0)));
+        assert_eq!(calculator.variables().get("ans"), Some(&Value::Float(12.0)));
+        assert_eq!(trace, calculator.trace("x = (1 + 2) * 4").unwrap());
+        let definition = calculator.calculate_traced("f(y) = y + x").unwrap();
+        assert_eq!(definition.result, None);
+        assert_eq!(calculator.calculate("f(1)").unwrap(), Some(Value::Float(13.0)));
+    }
+}
@@ -0,0 +1,378 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use num_rational::BigRational;
+
+use super::functions::{AngleMode, Function};
+use super::number::{format_float, Number, NumberError, Value};
+
+pub(crate) const BASE_UNITS: [&str; 7] = ["kg", "m", "s", "A", "K", "mol", "cd"];
+
+pub(crate) const BUILTIN_UNITS: &str = "\
+km = 1000 m
+cm = 0.01 m
+mm = 0.001 m
+um = 1e-6 m
+nm = 1e-9 m
+inch = 0.0254 m
+ft = 0.3048 m
+yd = 0.9144 m
+mi = 1609.344 m
+nmi = 1852 m
+g = 0.001 kg
+mg = 1e-6 kg
+t = 1000 kg
+lb = 0.45359237 kg
+oz = 0.028349523125 kg
+ms = 0.001 s
+min = 60 s
+h = 3600 s
+day = 86400 s
+week = 604800 s
+ha = 10000 m^2
+L = 0.001 m^3
+mL = 1e-6 m^3
+gal = 3.785411784 L
+mph = mi/h
+kn = nmi/h
+Hz = 1/s
+N = kg*m/s^2
+kN = 1000 N
+lbf = 4.4482216152605 N
+J = N*m
+kJ = 1000 J
+cal = 4.184 J
+kcal = 4184 J
+Wh = 3600 J
+kWh = 1000 Wh
+eV = 1.602176634e-19 J
+W = J/s
+kW = 1000 W
+hp = 745.69987158227022 W
+Pa = N/m^2
+kPa = 1000 Pa
+bar = 100000 Pa
+atm = 101325 Pa
+psi = 6894.757293168 Pa
+C = A*s
+V = W/A
+ohm = V/A
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct Dimension([i8; 7]);
+
+impl Dimension {
+    pub(crate) fn base(index: usize) -> Self {
+        let mut exponents = [0; 7];
+        exponents[index] = 1;
+        Dimension(exponents)
+    }
+
+    pub(crate) fn is_none(self) -> bool {
+        self.0 == [0; 7]
+    }
+
+    fn combine(self, other: Dimension, sign: i8) -> Option<Dimension> {
+        let mut exponents = self.0;
+        for (exponent, other) in exponents.iter_mut().zip(other.0) {
+            *exponent = exponent.checked_add(other.checked_mul(sign)?)?;
+        }
+        Some(Dimension(exponents))
+    }
+
+    fn powf(self, power: f64) -> Option<Dimension> {
+        let mut exponents = self.0;
+        for exponent in exponents.iter_mut() {
+            let scaled = f64::from(*exponent) * power;
+            if (scaled - scaled.round()).abs() > 1e-9 || scaled.abs() > f64::from(i8::MAX) {
+                return None;
+            }
+            *exponent = scaled.round() as i8;
+        }
+        Some(Dimension(exponents))
+    }
+
+    fn describe(self) -> String {
+        if self.is_none() { String::from("a plain number") } else { self.to_string() }
+    }
+}
+
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut numerator = Vec::new();
+        let mut denominator = Vec::new();
+        for (symbol, exponent) in BASE_UNITS.iter().zip(self.0) {
+            match exponent {
+                0 => {}
+                1 => numerator.push(symbol.to_string()),
+                -1 => denominator.push(symbol.to_string()),
+                exponent if exponent > 0 => numerator.push(format!("{}^{}", symbol, exponent)),
+                exponent => denominator.push(format!("{}^{}", symbol, -exponent)),
+            }
+        }
+        if numerator.is_empty() {
+            let inverted = BASE_UNITS.iter().zip(self.0)
+                .filter(|(_, exponent)| *exponent != 0)
+                .map(|(symbol, exponent)| format!("{}^{}", symbol, exponent))
+                .collect::<Vec<_>>();
+            return write!(f, "{}", inverted.join("*"));
+        }
+        write!(f, "{}", numerator.join("*"))?;
+        match denominator.len() {
+            0 => Ok(()),
+            1 => write!(f, "/{}", denominator[0]),
+            _ => write!(f, "/({})", denominator.join("*")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Unit {
+    pub(crate) factor: BigRational,
+    pub(crate) dimension: Dimension,
+}
+
+/// A number with a unit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantityValue {
+    /// The value in `unit`.
+    pub magnitude: Value,
+    /// The value in SI base units.
+    pub base: Value,
+    /// The display unit, such as `km/h`.
+    pub unit: String,
+    pub(crate) dimension: Dimension,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Quantity<N> {
+    pub(crate) value: N,
+    pub(crate) dimension: Dimension,
+}
+
+impl<N: Number> Quantity<N> {
+    fn plain(value: N) -> Self {
+        Quantity { value, dimension: Dimension::default() }
+    }
+
+    fn same_dimension(&self, other: &Self, verb: &str) -> Result<(), NumberError> {
+        if self.dimension == other.dimension {
+            Ok(())
+        }
+        else {
+            Err(NumberError::Dimension(format!(
+                "Cannot {} {} and {}", verb, self.dimension.describe(), other.dimension.describe()
+            )))
+        }
+    }
+}
+
+impl<N: Number> PartialOrd for Quantity<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self.dimension == other.dimension { self.value.partial_cmp(&other.value) } else { None }
+    }
+}
+
+impl<N: Number> Number for Quantity<N> {
+    fn parse_literal(literal: &str) -> Option<Self> {
+        N::parse_literal(literal).map(Self::plain)
+    }
+
+    fn from_f64(value: f64) -> Option<Self> {
+        N::from_f64(value).map(Self::plain)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Quantity(quantity) => {
+                Some(Quantity { value: N::from_value(&quantity.base)?, dimension: quantity.dimension })
+            }
+            value => N::from_value(value).map(Self::plain),
+        }
+    }
+
+    fn from_unit(unit: &Unit) -> Option<Self> {
+        let value = N::from_value(&Value::Rational(unit.factor.clone()))?;
+        Some(Quantity { value, dimension: unit.dimension })
+    }
+
+    fn to_f64(&self) -> f64 {
+        self.value.to_f64()
+    }
+
+    fn zero() -> Self {
+        Self::plain(N::zero())
+    }
+
+    fn one() -> Self {
+        Self::plain(N::one())
+    }
+
+    fn checked_add(&self, other: &Self) -> Result<Self, NumberError> {
+        self.same_dimension(other, "add")?;
+        Ok(Quantity { value: self.value.checked_add(&other.value)?, dimension: self.dimension })
+    }
+
+    fn checked_sub(&self, other: &Self) -> Result<Self, NumberError> {
+        self.same_dimension(other, "subtract")?;
+        Ok(Quantity { value: self.value.checked_sub(&other.value)?, dimension: self.dimension })
+    }
+
+    fn checked_mul(&self, other: &Self) -> Result<Self, NumberError> {
+        let dimension = self.dimension.combine(other.dimension, 1).ok_or(NumberError::Overflow)?;
+        Ok(Quantity { value: self.value.checked_mul(&other.value)?, dimension })
+    }
+
+    fn checked_div(&self, other: &Self) -> Result<Self, NumberError> {
+        let dimension = self.dimension.combine(other.dimension, -1).ok_or(NumberError::Overflow)?;
+        Ok(Quantity { value: self.value.checked_div(&other.value)?, dimension })
+    }
+
+    fn checked_pow(&self, exponent: &Self) -> Result<Self, NumberError> {
+        if !exponent.dimension.is_none() {
+            return Err(NumberError::Dimension(format!("Exponent must be a plain number, not {}", exponent.dimension)));
+        }
+        if self.dimension.is_none() {
+            return self.value.checked_pow(&exponent.value).map(Self::plain);
+        }
+        let power = exponent.value.to_f64();
+        let dimension = self.dimension.powf(power)
+            .ok_or_else(|| NumberError::Dimension(format!("Cannot raise {} to the power {}", self.dimension, format_float(power))))?;
+        Ok(Quantity { value: self.value.checked_pow(&exponent.value)?, dimension })
+    }
+
+    fn negate(&self) -> Self {
+        Quantity { value: self.value.negate(), dimension: self.dimension }
+    }
+
+    fn abs(&self) -> Self {
+        Quantity { value: self.value.abs(), dimension: self.dimension }
+    }
+
+    fn floor(&self) -> Self {
+        Quantity { value: self.value.floor(), dimension: self.dimension }
+    }
+
+    fn ceil(&self) -> Self {
+        Quantity { value: self.value.ceil(), dimension: self.dimension }
+    }
+
+    fn round(&self) -> Self {
+        Quantity { value: self.value.round(), dimension: self.dimension }
+    }
+
+    fn checked_convert(&self, unit: &Self) -> Result<Self, NumberError> {
+        if self.dimension != unit.dimension {
+            return Err(NumberError::Dimension(format!(
+                "Cannot convert {} to {}", self.dimension.describe(), unit.dimension.describe()
+            )));
+        }
+        Ok(self.clone())
+    }
+
+    fn checked_polar(&self, angle: &Self, angle_mode: AngleMode) -> Result<Self, NumberError> {
+        if !self.dimension.is_none() || !angle.dimension.is_none() {
+            return Err(NumberError::Dimension(String::from("Polar form needs plain numbers")));
+        }
+        self.value.checked_polar(&angle.value, angle_mode).map(Self::plain)
+    }
+
+    fn check_compatible(&self, other: &Self) -> Result<(), NumberError> {
+        self.same_dimension(other, "compare")?;
+        self.value.check_compatible(&other.value)
+    }
+
+    fn imaginary_unit() -> Option<Self> {
+        N::imaginary_unit().map(Self::plain)
+    }
+
+    fn inexact(what: &str) -> Option<NumberError> {
+        N::inexact(what)
+    }
+
+    fn apply_function(function: Function, arguments: &[Self], angle_mode: AngleMode) -> Option<Result<Self, NumberError>> {
+        if arguments.iter().any(|argument| !argument.dimension.is_none()) {
+            let half = N::from_f64(0.5).map(Self::plain)?;
+            return (function == Function::Sqrt).then(|| arguments[0].checked_pow(&half));
+        }
+        let values = arguments.iter().map(|argument| argument.value.clone()).collect::<Vec<N>>();
+        N::apply_function(function, &values, angle_mode).map(|result| result.map(Self::plain))
+    }
+
+    fn is_real(&self) -> bool {
+        self.value.is_real()
+    }
+
+    fn is_integer_only() -> bool {
+        N::is_integer_only()
+    }
+
+    fn is_dimensionless(&self) -> bool {
+        self.dimension.is_none()
+    }
+
+    fn is_zero(&self) -> bool {
+        self.value.is_zero()
+    }
+
+    fn is_integer(&self) -> bool {
+        self.value.is_integer()
+    }
+
+    fn is_negative(&self) -> bool {
+        self.value.is_negative()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Backend, CalcError, Calculator};
+
+    fn format(calculator: &Calculator, input: &str) -> String {
+        calculator.evaluate_expression(input).unwrap_or_else(|error| panic!("{} gave {}", input, error)).to_string()
+    }
+
+    #[test]
+    fn converts_between_units() {
+        let mut calculator = Calculator::default();
+        calculator.set_backend(Backend::Rational);
+        for (input, expected) in [
+            ("(1 mi) in km", "25146/15625 km"),
+            ("100 km/h in m/s", "250/9 m/s"),
+            ("1 kWh in J", "3600000 J"),
+            ("3 h + 30 min", "12600 s"),
+            ("(2 m)*(3 m) in ha", "3/5000 ha"),
+        ] {
+            assert_eq!(format(&calculator, input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn takes_roots_of_even_dimensions() {
+        let mut calculator = Calculator::default();
+        for backend in [Backend::Float, Backend::Rational] {
+            calculator.set_backend(backend);
+            for (input, expected) in [("sqrt(16 m^2)", "4 m"), ("(4 m^2)^0.5", "2 m"), ("(8 m^3)^(1/3)", "2 m"), ("(4 m^2)^1.5", "8 m^3")] {
+                assert_eq!(format(&calculator, input), expected, "{} in {:?}", input, backend);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_mismatched_dimensions() {
+        let calculator = Calculator::default();
+        for (input, message) in [
+            ("2 m + 3 s", "Cannot add m and s"),
+            ("(5 km) in s", "Cannot convert m to s"),
+            ("sqrt(9 m)", "Cannot raise m to the power 0.5"),
+            ("2^(1 m)", "Exponent must be a plain number, not m"),
+            ("sin(1 m)", "sin needs a plain number"),
+        ] {
+            match calculator.evaluate_expression(input) {
+                Err(CalcError::Dimension { message: found, .. }) => assert_eq!(found, message, "{}", input),
+                result => panic!("{} gave {:?}", input, result),
+            }
+        }
+    }
+}
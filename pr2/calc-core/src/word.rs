@@ -0,0 +1,253 @@
+use num_traits::ToPrimitive;
+
+use super::number::{parse_integer, Number, NumberError, Value};
+use super::token::BitwiseOperator;
+
+/// The integer width of the programmer backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WordSize {
+    /// Width in bits, one of [`WordSize::BITS`].
+    pub bits: u32,
+    /// Whether values are signed two's complement.
+    pub signed: bool,
+}
+
+impl Default for WordSize {
+    fn default() -> Self {
+        WordSize { bits: 64, signed: true }
+    }
+}
+
+impl WordSize {
+    /// The supported widths.
+    pub const BITS: [u32; 4] = [8, 16, 32, 64];
+
+    fn mask(self) -> u128 {
+        (1 << self.bits) - 1
+    }
+
+    pub(crate) fn wrap(self, value: i128) -> i128 {
+        let pattern = value as u128 & self.mask();
+        if self.signed && pattern >> (self.bits - 1) == 1 {
+            pattern as i128 - (1 << self.bits)
+        }
+        else {
+            pattern as i128
+        }
+    }
+
+    /// Formats a value in base 2, 8, 10 or 16. Bases other than 10 show the bit pattern.
+    pub fn format(self, value: i128, radix: u32) -> String {
+        let pattern = value as u128 & self.mask();
+        match radix {
+            2 => format!("{:b}", pattern),
+            8 => format!("{:o}", pattern),
+            16 => format!("{:X}", pattern),
+            _ => value.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Word<const BITS: u32, const SIGNED: bool>(pub(crate) i128);
+
+impl<const BITS: u32, const SIGNED: bool> Word<BITS, SIGNED> {
+    const SIZE: WordSize = WordSize { bits: BITS, signed: SIGNED };
+
+    fn wrapped(value: i128) -> Self {
+        Word(Self::SIZE.wrap(value))
+    }
+
+    fn shift_amount(&self) -> Result<u32, NumberError> {
+        if self.0 < 0 {
+            return Err(NumberError::Domain(String::from("Shift amount must not be negative")));
+        }
+        Ok(self.0.min(127) as u32)
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> Number for Word<BITS, SIGNED> {
+    fn parse_literal(literal: &str) -> Option<Self> {
+        parse_integer(literal).map(Self::wrapped)
+    }
+
+    fn from_f64(value: f64) -> Option<Self> {
+        (value.is_finite() && value.fract() == 0.0).then(|| Self::wrapped(value as i128))
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Float(value) => Self::from_f64(*value),
+            Value::Decimal(value) => value.trunc().to_i128().map(Self::wrapped),
+            Value::Rational(value) => value.to_integer().to_i128().map(Self::wrapped),
+            Value::Integer { value, .. } => Some(Self::wrapped(*value)),
+            Value::Quantity(quantity) => Self::from_value(&quantity.base),
+            Value::Complex(_) => Self::from_f64(value.to_f64()),
+            Value::Expression(_) | Value::Matrix { .. } => None,
+        }
+    }
+
+    fn to_f64(&self) -> f64 {
+        self.0 as f64
+    }
+
+    fn zero() -> Self {
+        Word(0)
+    }
+
+    fn one() -> Self {
+        Word(1)
+    }
+
+    fn checked_add(&self, other: &Self) -> Result<Self, NumberError> {
+        Ok(Self::wrapped(self.0.wrapping_add(other.0)))
+    }
+
+    fn checked_sub(&self, other: &Self) -> Result<Self, NumberError> {
+        Ok(Self::wrapped(self.0.wrapping_sub(other.0)))
+    }
+
+    fn checked_mul(&self, other: &Self) -> Result<Self, NumberError> {
+        Ok(Self::wrapped(self.0.wrapping_mul(other.0)))
+    }
+
+    fn checked_div(&self, other: &Self) -> Result<Self, NumberError> {
+        Ok(Self::wrapped(self.0 / other.0))
+    }
+
+    fn checked_pow(&self, exponent: &Self) -> Result<Self, NumberError> {
+        if exponent.0 < 0 {
+            return Err(NumberError::Domain(String::from("Negative exponents are not allowed in programmer mode")));
+        }
+        let mut result: i128 = 1;
+        let mut base = self.0;
+        let mut exponent = exponent.0;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.wrapping_mul(base);
+            }
+            base = base.wrapping_mul(base);
+            exponent >>= 1;
+        }
+        Ok(Self::wrapped(result))
+    }
+
+    fn negate(&self) -> Self {
+        Self::wrapped(self.0.wrapping_neg())
+    }
+
+    fn abs(&self) -> Self {
+        Self::wrapped(self.0.wrapping_abs())
+    }
+
+    fn floor(&self) -> Self {
+        *self
+    }
+
+    fn ceil(&self) -> Self {
+        *self
+    }
+
+    fn round(&self) -> Self {
+        *self
+    }
+
+    fn checked_bitwise(&self, other: &Self, operator: BitwiseOperator) -> Result<Self, NumberError> {
+        let value = match operator {
+            BitwiseOperator::And => self.0 & other.0,
+            BitwiseOperator::Or => self.0 | other.0,
+            BitwiseOperator::Xor => self.0 ^ other.0,
+            BitwiseOperator::ShiftLeft => self.0 << other.shift_amount()?,
+            BitwiseOperator::ShiftRight => self.0 >> other.shift_amount()?,
+        };
+        Ok(Self::wrapped(value))
+    }
+
+    fn checked_not(&self) -> Result<Self, NumberError> {
+        Ok(Self::wrapped(!self.0))
+    }
+
+    fn inexact(what: &str) -> Option<NumberError> {
+        Some(NumberError::Domain(format!("{} is not available in programmer mode", what)))
+    }
+
+    fn is_integer_only() -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Backend, CalcError, Calculator, Value, WordSize};
+
+    fn integer(calculator: &Calculator, input: &str) -> i128 {
+        match calculator.evaluate_expression(input) {
+            Ok(Value::Integer { value, .. }) => value,
+            result => panic!("{} gave {:?}", input, result),
+        }
+    }
+
+    fn programmer(bits: u32, signed: bool) -> Calculator {
+        let mut calculator = Calculator::default();
+        calculator.set_backend(Backend::Programmer);
+        calculator.set_word_size(WordSize { bits, signed });
+        calculator
+    }
+
+    #[test]
+    fn wraps_at_the_word_size() {
+        for (bits, signed, input, expected) in [
+            (8, false, "255 + 1", 0),
+            (8, true, "127 + 1", -128),
+            (8, false, "0 - 1", 255),
+            (16, false, "0xFFFF + 2", 1),
+            (16, true, "0x7FFF + 1", -32768),
+            (32, true, "0xFFFFFFFF", -1),
+            (32, false, "0xFFFFFFFF", 4294967295),
+            (64, true, "9223372036854775807 + 1", i128::from(i64::MIN)),
+            (64, false, "0 - 1", i128::from(u64::MAX)),
+            (8, true, "16 * 16", 0),
+            (8, false, "2^9", 0),
+        ] {
+            assert_eq!(integer(&programmer(bits, signed), input), expected, "{} at {} bits, signed {}", input, bits, signed);
+        }
+    }
+
+    #[test]
+    fn parses_radix_literals() {
+        let calculator = programmer(32, true);
+        for (input, expected) in [("0x1F", 31), ("0b1010", 10), ("0o17", 15), ("0xff + 0b1 + 0o7", 263), ("7 / 2", 3)] {
+            assert_eq!(integer(&calculator, input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn applies_bitwise_operators_and_shifts() {
+        let calculator = programmer(8, false);
+        for (input, expected) in [
+            ("0b1100 & 0b1010", 8),
+            ("0b1100 | 0b1010", 14),
+            ("0b1100 xor 0b1010", 6),
+            ("~0", 255),
+            ("1 << 7", 128),
+            ("1 << 8", 0),
+            ("0x80 >> 3", 16),
+            ("1 + 2 << 1", 6),
+        ] {
+            assert_eq!(integer(&calculator, input), expected, "{}", input);
+        }
+        assert_eq!(integer(&programmer(8, true), "0x80 >> 1"), -64);
+        assert!(matches!(programmer(8, true).evaluate_expression("1 << (0 - 1)"), Err(CalcError::Domain { .. })));
+    }
+
+    #[test]
+    fn rejects_inexact_values() {
+        let calculator = programmer(64, true);
+        for input in ["pi", "e * 2", "5%", "90°", "sin(1)", "sqrt(16)"] {
+            match calculator.evaluate_expression(input) {
+                Err(CalcError::Domain { message, .. }) => assert!(message.ends_with("is not available in programmer mode"), "{}", message),
+                result => panic!("{} gave {:?}", input, result),
+            }
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use eframe::egui::{Color32, Grid, Label, RichText, ScrollArea, TextEdit, Ui};
+
+use calc_core::{Backend, CalcError, Calculator, DisplayOptions, Value, WordSize};
+
+const DEFAULT_NAME: &str = "data";
+const DELIMITERS: [char; 3] = ['\t', ';', ','];
+const INPUT_ROWS: usize = 8;
+const STATISTICS: [(&str, &str); 9] = [
+    ("mean", "mean({})"),
+    ("median", "median({})"),
+    ("mode", "mode({})"),
+    ("stdev", "stdev({})"),
+    ("variance", "variance({})"),
+    ("min", "min({})"),
+    ("Q1", "percentile({}, 25)"),
+    ("Q3", "percentile({}, 75)"),
+    ("max", "max({})"),
+];
+
+struct Column {
+    name: String,
+    values: Vec<f64>,
+}
+
+#[derive(Default)]
+struct Summary {
+    rows: Vec<(String, Vec<String>)>,
+    regression: Option<Vec<(String, String)>>,
+}
+
+#[derive(Default)]
+pub(crate) struct DataPanel {
+    text: String,
+    path: String,
+    columns: Vec<Column>,
+    summary: Summary,
+    summarized: Option<(Backend, WordSize, DisplayOptions)>,
+    error: Option<String>,
+}
+
+impl DataPanel {
+    pub(crate) fn show(&mut self, ui: &mut Ui, calculator: &mut Calculator, options: &DisplayOptions) {
+        ui.horizontal(|ui| {
+            ui.add(TextEdit::singleline(&mut self.path).hint_text("values.csv").desired_width(200.0));
+            if ui.button("import").clicked() {
+                match std::fs::read_to_string(self.path.trim()) {
+                    Ok(text) => {
+                        self.text = text;
+                        self.load(calculator);
+                    }
+                    Err(error) => self.error = Some(format!("Cannot read {}: {}", self.path.trim(), error)),
+                }
+            }
+        });
+        ScrollArea::vertical().id_salt("data_input").max_height(ui.available_height() / 2.0).show(ui, |ui| {
+            ui.add(TextEdit::multiline(&mut self.text)
+                .hint_text("Paste a column of numbers or CSV rows")
+                .desired_rows(INPUT_ROWS)
+                .desired_width(f32::INFINITY)
+                .code_editor());
+        });
+        ui.horizontal(|ui| {
+            if ui.button("load").clicked() {
+                self.load(calculator);
+            }
+            let names = self.columns.iter()
+                .map(|column| format!("{} ({})", column.name, column.values.len()))
+                .collect::<Vec<_>>();
+            ui.add(Label::new(names.join(", ")).truncate());
+        });
+        if let Some(error) = &self.error {
+            ui.label(RichText::new(error).color(Color32::RED));
+        }
+        if self.columns.is_empty() {
+            return;
+        }
+        let key = (calculator.backend(), calculator.word_size(), *options);
+        if self.summarized != Some(key) {
+            self.summary = self.summarize(calculator, options);
+            self.summarized = Some(key);
+        }
+        ScrollArea::both().id_salt("data_summary").show(ui, |ui| {
+            Grid::new("data_summary_grid").num_columns(self.columns.len() + 1).striped(true).show(ui, |ui| {
+                ui.label("");
+                for column in &self.columns {
+                    ui.strong(&column.name);
+                }
+                ui.end_row();
+                ui.label("n");
+                for column in &self.columns {
+                    ui.monospace(column.values.len().to_string());
+                }
+                ui.end_row();
+                for (label, cells) in &self.summary.rows {
+                    ui.label(label);
+                    for cell in cells {
+                        ui.monospace(cell);
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(regression) = &self.summary.regression {
+                ui.separator();
+                ui.label(format!("{} = slope * {} + intercept", self.columns[1].name, self.columns[0].name));
+                Grid::new("data_regression_grid").num_columns(2).striped(true).show(ui, |ui| {
+                    for (label, cell) in regression {
+                        ui.label(label);
+                        ui.monospace(cell);
+                        ui.end_row();
+                    }
+                });
+            }
+        });
+    }
+
+    fn load(&mut self, calculator: &mut Calculator) {
+        self.columns.clear();
+        self.summarized = None;
+        self.error = None;
+        let columns = match parse_table(&self.text) {
+            Ok(columns) => columns,
+            Err(error) => {
+                self.error = Some(error);
+                return;
+            }
+        };
+        for mut column in columns {
+            if let Err(error) = store_column(calculator, &mut column) {
+                self.error = Some(error.to_string());
+                return;
+            }
+            self.columns.push(column);
+        }
+    }
+
+    fn summarize(&self, calculator: &Calculator, options: &DisplayOptions) -> Summary {
+        let evaluate = |expression: String| match calculator.evaluate_expression(&expression) {
+            Ok(value) => value.format(options),
+            Err(_) => String::from("-"),
+        };
+        let rows = STATISTICS.iter()
+            .map(|(label, template)| {
+                let cells = self.columns.iter().map(|column| evaluate(template.replace("{}", &column.name))).collect();
+                (label.to_string(), cells)
+            })
+            .collect();
+        let regression = match &self.columns[..] {
+            [x, y, ..] => {
+                let arguments = format!("{}, {}", x.name, y.name);
+                let (slope, intercept) = match calculator.evaluate_expression(&format!("linreg({})", arguments)) {
+                    Ok(Value::Matrix { elements, .. }) => (elements[0].format(options), elements[1].format(options)),
+                    _ => (String::from("-"), String::from("-")),
+                };
+                Some(vec![
+                    (String::from("slope"), slope),
+                    (String::from("intercept"), intercept),
+                    (String::from("r"), evaluate(format!("corr({})", arguments))),
+                ])
+            }
+            _ => None,
+        };
+        Summary { rows, regression }
+    }
+}
+
+fn store_column(calculator: &mut Calculator, column: &mut Column) -> Result<(), CalcError> {
+    let elements = column.values.iter().map(f64::to_string).collect::<Vec<_>>();
+    let value = calculator.evaluate_expression(&format!("[{}]", elements.join("; ")))?;
+    if !calculator.defines_unit(&column.name) {
+        match calculator.set_variable(&column.name, value.clone()) {
+            Err(CalcError::ReservedName { .. }) => {}
+            stored => return stored,
+        }
+    }
+    column.name = format!("{}_{}", column.name, DEFAULT_NAME);
+    calculator.set_variable(&column.name, value)
+}
+
+fn parse_table(text: &str) -> Result<Vec<Column>, String> {
+    let delimiter = DELIMITERS.into_iter().find(|delimiter| text.contains(*delimiter));
+    let mut lines = text.lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, split_line(line, delimiter)))
+        .filter(|(_, cells)| !cells.is_empty())
+        .peekable();
+    let Some((_, first)) = lines.peek() else {
+        return Err(String::from("No values to load"));
+    };
+    let header = if first.iter().any(|cell| !cell.is_empty() && parse_cell(cell, delimiter).is_none()) {
+        lines.next().map(|(_, cells)| cells)
+    }
+    else {
+        None
+    };
+    let Some(width) = header.as_ref().or(lines.peek().map(|(_, cells)| cells)).map(Vec::len) else {
+        return Err(String::from("No values to load"));
+    };
+    let mut columns = (0..width).map(|_| Column { name: String::new(), values: Vec::new() }).collect::<Vec<_>>();
+    for (line, cells) in lines {
+        if cells.len() != width {
+            return Err(format!("Line {}: expected {} values, found {}", line, width, cells.len()));
+        }
+        for (index, cell) in cells.iter().enumerate() {
+            if cell.is_empty() {
+                return Err(format!("Line {}, column {}: missing value", line, index + 1));
+            }
+            let value = parse_cell(cell, delimiter).ok_or_else(|| format!("Line {}: {} is not a number", line, cell))?;
+            columns[index].values.push(value);
+        }
+    }
+    if columns[0].values.is_empty() {
+        return Err(String::from("No values to load"));
+    }
+    for index in 0..width {
+        let heading = header.as_ref().and_then(|header| header.get(index)).map(|heading| column_name(heading));
+        let mut name = match heading {
+            Some(name) if !name.is_empty() => name,
+            _ if width == 1 => String::from(DEFAULT_NAME),
+            _ => format!("{}{}", DEFAULT_NAME, index + 1),
+        };
+        if columns[..index].iter().any(|column| column.name == name) {
+            name = format!("{}_{}", name, index + 1);
+        }
+        columns[index].name = name;
+    }
+    Ok(columns)
+}
+
+fn split_line(line: &str, delimiter: Option<char>) -> Vec<String> {
+    let cells: Vec<&str> = match delimiter {
+        Some(delimiter) => line.split(delimiter).collect(),
+        None => line.split_whitespace().collect(),
+    };
+    if cells.iter().all(|cell| cell.trim().is_empty()) {
+        return Vec::new();
+    }
+    cells.into_iter().map(|cell| cell.trim().trim_matches('"').trim().to_string()).collect()
+}
+
+fn parse_cell(cell: &str, delimiter: Option<char>) -> Option<f64> {
+    let cell = if delimiter == Some(',') { cell.to_string() } else { cell.replace(',', ".") };
+    cell.parse::<f64>().ok().filter(|value| value.is_finite())
+}
+
+fn column_name(heading: &str) -> String {
+    let name = heading.split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+    if name.starts_with(|c: char| c.is_alphabetic()) { name } else { String::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use calc_core::Calculator;
+
+    use super::{parse_table, DataPanel};
+
+    fn parsed(text: &str) -> Vec<(String, Vec<f64>)> {
+        parse_table(text).unwrap().into_iter().map(|column| (column.name, column.values)).collect()
+    }
+
+    #[test]
+    fn reads_headers_and_delimiters() {
+        assert_eq!(parsed("x,y\n1,2\n3,4.5\n"), [(String::from("x"), vec![1.0, 3.0]), (String::from("y"), vec![2.0, 4.5])]);
+        assert_eq!(parsed("height (cm);mass\n1,5;2\n\n3;4"), [
+            (String::from("height_cm"), vec![1.5, 3.0]),
+            (String::from("mass"), vec![2.0, 4.0]),
+        ]);
+        assert_eq!(parsed("1\n2\n3"), [(String::from("data"), vec![1.0, 2.0, 3.0])]);
+        assert_eq!(parsed("1 2\n3 4"), [(String::from("data1"), vec![1.0, 3.0]), (String::from("data2"), vec![2.0, 4.0])]);
+        assert_eq!(parsed("a,a\n1,2"), [(String::from("a"), vec![1.0]), (String::from("a_2"), vec![2.0])]);
+    }
+
+    #[test]
+    fn rejects_gaps_instead_of_misaligning_rows() {
+        assert_eq!(parse_table("x,y\n1,2\n3,\n5,6").err().unwrap(), "Line 3, column 2: missing value");
+        assert_eq!(parse_table("x,y\n1,2\n3\n5,6").err().unwrap(), "Line 3: expected 2 values, found 1");
+        assert_eq!(parse_table("x,y\n1,2,3").err().unwrap(), "Line 2: expected 2 values, found 3");
+        assert_eq!(parse_table("x\n1\ntwo").err().unwrap(), "Line 3: two is not a number");
+        assert_eq!(parse_table("x,y\n").err().unwrap(), "No values to load");
+    }
+
+    #[test]
+    fn renames_reserved_headers() {
+        let mut calculator = Calculator::default();
+        let mut panel = DataPanel { text: String::from("e,sin,y,m,min\n1,2,3,4,5\n4,5,6,7,8"), ..Default::default() };
+        panel.load(&mut calculator);
+        assert_eq!(panel.error, None);
+        let names = panel.columns.iter().map(|column| column.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["e_data", "sin_data", "y", "m_data", "min_data"]);
+        assert!(calculator.variables().contains_key("sin_data"));
+        assert!(!calculator.variables().contains_key("m"));
+        assert_eq!(calculator.evaluate_expression("(2 min) in s").unwrap().to_string(), "120 s");
+    }
+}
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+const MAX_ENTRIES: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct HistoryEntry {
+    pub(crate) expression: String,
+    pub(crate) result: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct History {
+    entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    pub(crate) const STORAGE_KEY: &'static str = "history";
+
+    pub(crate) fn push(&mut self, expression: String, result: String) {
+        self.entries.push(HistoryEntry { expression, result });
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+    }
+
+    pub(crate) fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
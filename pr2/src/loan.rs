@@ -0,0 +1,87 @@
+use eframe::egui::{Color32, Grid, RichText, ScrollArea, TextEdit, Ui};
+
+use calc_core::{CalcError, Calculator, DisplayOptions, Value};
+
+const HEADERS: [&str; 5] = ["#", "payment", "interest", "principal", "balance"];
+
+#[derive(Default)]
+pub(crate) struct LoanPanel {
+    rate: String,
+    periods: String,
+    principal: String,
+    schedule: Option<Value>,
+    totals: Vec<(&'static str, Value)>,
+    error: Option<String>,
+}
+
+impl LoanPanel {
+    pub(crate) fn show(&mut self, ui: &mut Ui, calculator: &Calculator, options: &DisplayOptions) {
+        Grid::new("loan_inputs").num_columns(2).show(ui, |ui| {
+            for (label, text, hint) in [
+                ("rate per period", &mut self.rate, "5%/12"),
+                ("periods", &mut self.periods, "360"),
+                ("principal", &mut self.principal, "200000"),
+            ] {
+                ui.label(label);
+                ui.add(TextEdit::singleline(text).hint_text(hint).desired_width(160.0));
+                ui.end_row();
+            }
+        });
+        if ui.button("schedule").clicked() {
+            if let Err(error) = self.calculate(calculator) {
+                self.schedule = None;
+                self.totals.clear();
+                self.error = Some(error.to_string());
+            }
+        }
+        if let Some(error) = &self.error {
+            ui.label(RichText::new(error).color(Color32::RED));
+        }
+        let Some(Value::Matrix { columns, elements, .. }) = &self.schedule else {
+            return;
+        };
+        Grid::new("loan_totals").num_columns(2).show(ui, |ui| {
+            for (label, total) in &self.totals {
+                ui.label(*label);
+                ui.monospace(total.format(options));
+                ui.end_row();
+            }
+        });
+        ui.separator();
+        ScrollArea::both().id_salt("loan_schedule").show(ui, |ui| {
+            Grid::new("loan_schedule_grid").num_columns(*columns).striped(true).show(ui, |ui| {
+                for header in HEADERS {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for row in elements.chunks(*columns) {
+                    for cell in row {
+                        ui.monospace(cell.format(options));
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    fn calculate(&mut self, calculator: &Calculator) -> Result<(), CalcError> {
+        self.error = None;
+        let inputs = [&self.rate, &self.periods, &self.principal]
+            .map(|text| format!("({})", text.trim()))
+            .join(", ");
+        let schedule = calculator.evaluate_expression(&format!("amort({})", inputs))?;
+        let Value::Matrix { columns, elements, .. } = &schedule else {
+            return Ok(());
+        };
+        self.totals.clear();
+        for (label, column) in [("total paid", 1), ("total interest", 2)] {
+            let mut total = elements[column].clone();
+            for row in elements.chunks(*columns).skip(1) {
+                total = calculator.apply_operator("+", &total, &row[column])?;
+            }
+            self.totals.push((label, total));
+        }
+        self.schedule = Some(schedule);
+        Ok(())
+    }
+}
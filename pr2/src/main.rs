@@ -1,119 +1,1073 @@
-mod calculator;
-use eframe::egui::{self, Button, CentralPanel, TextEdit};
-use eframe::{App, Frame};
-use egui::{Grid, Vec2};
-use crate::calculator::Calculator;
+mod data;
+mod history;
+mod loan;
+mod memory;
+mod plot;
+mod rpn;
+mod steps;
+use eframe::egui::{self, Button, CentralPanel, DragValue, Event, Key, Label, RichText, ScrollArea, SidePanel, TextEdit};
+use eframe::{App, CreationContext, Frame, Storage};
+use egui::text::{LayoutJob, TextFormat};
+use egui::{Color32, FontId, Grid, Stroke, Vec2};
+use calc_core::{Arity, Backend, CalcError, Calculator, ComplexDisplay, DisplayOptions, RationalDisplay, Span, Value, WordSize};
+use crate::data::DataPanel;
+use crate::history::History;
+use crate::loan::LoanPanel;
+use crate::memory::{Memory, SavedMemory};
+use crate::plot::Plotter;
+use crate::rpn::RpnStack;
+use crate::steps::StepsPanel;
 
 const WINDOW_WIDTH: f32 = 300.0;
-const WINDOW_HEIGHT: f32 = 300.0;
+const WINDOW_HEIGHT: f32 = 420.0;
+const VARIABLES_PANEL_WIDTH: f32 = 170.0;
+const HISTORY_PANEL_WIDTH: f32 = 190.0;
+const ERROR_FONT_SIZE: f32 = 12.0;
+const MATRIX_GRID_HEIGHT: f32 = 120.0;
+const CONSTANTS: [&str; 3] = ["pi", "e", "i"];
+const IMAGINARY: &str = "i";
+const RADIX_PREFIXES: [&str; 3] = ["0x", "0o", "0b"];
+const HEX_KEYS: [&str; 6] = ["xA", "xB", "xC", "xD", "xE", "xF"];
+const WORD_OPERATORS: [&str; 5] = ["xor", "<<", ">>", ".*", "./"];
+const MULTI_CHAR_TOKENS: [&str; 8] = ["0x", "0o", "0b", " xor ", "<<", ">>", ".*", "./"];
+const BASES: [(&str, u32); 4] = [("HEX", 16), ("DEC", 10), ("OCT", 8), ("BIN", 2)];
+const APP_NAME: &str = "Calculator";
+const UNITS_FILE: &str = "units.conf";
+
+const BASIC_BUTTONS: [[&str; 4]; 7] = [
+    ["(", ")", "±", "sci"],
+    ["7", "8", "9", "+"],
+    ["4", "5", "6", "-"],
+    ["1", "2", "3", "*"],
+    ["0", ".", "=", "/"],
+    ["C", "<-", "%", "^"],
+    [",", "pi", "mat", "fin"],
+];
+
+const SCIENTIFIC_BUTTONS: [[&str; 4]; 6] = [
+    ["(", ")", ",", "123"],
+    ["sin", "cos", "tan", "!"],
+    ["asin", "acos", "atan", "abs"],
+    ["sqrt", "ln", "log10", "log"],
+    ["floor", "ceil", "round", "min"],
+    ["pi", "e", "angle", "max"],
+];
+
+const PROGRAMMER_BUTTONS: [[&str; 4]; 8] = [
+    ["xA", "xB", "xC", "123"],
+    ["xD", "xE", "xF", "~"],
+    ["7", "8", "9", "&"],
+    ["4", "5", "6", "|"],
+    ["1", "2", "3", "xor"],
+    ["0", "0x", "0o", "0b"],
+    ["<<", ">>", "!", "="],
+    ["(", ")", "C", "<-"],
+];
+
+const COMPLEX_BUTTONS: [[&str; 4]; 6] = [
+    ["(", ")", ",", "123"],
+    ["i", "∠", "°", "abs"],
+    ["arg", "conj", "re", "im"],
+    ["sin", "cos", "tan", "sqrt"],
+    ["asin", "acos", "atan", "ln"],
+    ["pi", "e", "angle", "log"],
+];
+
+const MATRIX_BUTTONS: [[&str; 4]; 6] = [
+    ["[", "]", ",", ";"],
+    ["7", "8", "9", ".*"],
+    ["4", "5", "6", "./"],
+    ["1", "2", "3", "det"],
+    ["0", ".", "=", "inv"],
+    ["123", "<-", "transpose", "linsolve"],
+];
+
+const FINANCE_BUTTONS: [[&str; 4]; 7] = [
+    ["pmt", "pv", "fv", "123"],
+    ["npv", "irr", "simple", "compound"],
+    ["(", ")", ",", "%"],
+    ["7", "8", "9", "/"],
+    ["4", "5", "6", "*"],
+    ["1", "2", "3", "-"],
+    ["0", ".", "=", "+"],
+];
+
+const RPN_BUTTONS: [[&str; 4]; 6] = [
+    ["swap", "roll", "dup", "drop"],
+    ["7", "8", "9", "/"],
+    ["4", "5", "6", "*"],
+    ["1", "2", "3", "-"],
+    ["0", ".", "±", "+"],
+    ["C", "sci", "^", "enter"],
+];
+const MEMORY_BUTTONS: [&str; 4] = ["MC", "MR", "M+", "M-"];
+const RPN_OPERATORS: [&str; 13] = ["+", "-", "*", "/", "^", "&", "|", "∠", "xor", "<<", ">>", ".*", "./"];
+const STACK_COMMANDS: [&str; 4] = ["swap", "roll", "dup", "drop"];
+
+#[derive(Default, PartialEq)]
+enum ButtonPage {
+    #[default]
+    Basic,
+    Scientific,
+    Programmer,
+    Complex,
+    Matrix,
+    Finance,
+}
 
 #[derive(Default)]
 struct CalculatorApp {
     calculator: Calculator,
     display: String,
-    current_input: String,
     expression: String,
-    operator: Option<char>,
-    result: Option<f32>,
+    result: Option<Value>,
+    error: Option<CalcError>,
+    notice: Option<String>,
+    display_options: DisplayOptions,
+    page: ButtonPage,
+    variable_name: String,
+    definition: String,
+    history: History,
+    memory: Memory,
+    slot_name: String,
+    plotter: Plotter,
+    plot_open: bool,
+    data_panel: DataPanel,
+    data_open: bool,
+    loan_panel: LoanPanel,
+    loan_open: bool,
+    rpn: bool,
+    stack: RpnStack,
+    steps: StepsPanel,
 }
 
 impl CalculatorApp {
+    fn new(creation_context: &CreationContext<'_>) -> Self {
+        let history = creation_context.storage
+            .and_then(|storage| eframe::get_value(storage, History::STORAGE_KEY))
+            .unwrap_or_default();
+        let memory: SavedMemory = creation_context.storage
+            .and_then(|storage| eframe::get_value(storage, Memory::STORAGE_KEY))
+            .unwrap_or_default();
+        let mut app = CalculatorApp { history, ..Default::default() };
+        app.load_units();
+        app.memory = Memory::restore(&memory, &mut app.calculator);
+        app
+    }
+
+    fn load_units(&mut self) {
+        let Some(path) = eframe::storage_dir(APP_NAME).map(|dir| dir.join(UNITS_FILE)) else {
+            return;
+        };
+        let source = match std::fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return,
+            Err(error) => {
+                self.notice = Some(format!("Cannot read {}: {}", path.display(), error));
+                return;
+            }
+        };
+        if let Err((line, error)) = self.calculator.load_units(&source) {
+            self.notice = Some(format!("{}:{}: {}", path.display(), line, error));
+        }
+    }
 
     fn handle_input(&mut self, input: &str) {
-        if input == "C" {
+        self.error = None;
+        if MEMORY_BUTTONS.contains(&input) {
+            self.handle_memory(input);
+            return;
+        }
+        self.result = None;
+        if self.rpn && self.handle_rpn(input) {
+            self.update_display();
+        }
+        else if input == "C" {
             self.clear();
         }
         else if input == "<-" {
-            if !self.expression.is_empty() {
-                self.expression.pop();
-                self.update_display();
-            }
+            self.backspace();
+            self.update_display();
         }
         else if input == "=" {
-            if !self.expression.is_empty() && self.expression.chars().last().unwrap().is_digit(10) {
-                let result = self.calculator.calculate(self.expression.clone());
+            if !self.expression.is_empty() {
+                let result = self.steps.calculate(&mut self.calculator, &self.expression);
                 match result{
-                    Ok(res) => {self.expression = res.to_string(); self.update_display();},
-                    Err(error) => {
-                        self.expression = error.to_string();
+                    Ok(Some(res)) => {
+                        let formatted = res.format(&self.display_options);
+                        self.history.push(self.expression.clone(), formatted);
+                        self.expression = self.operand_text(&res);
+                        self.result = Some(res);
                         self.update_display();
+                    },
+                    Ok(None) => {
                         self.expression.clear();
-                    }
+                        self.update_display();
+                    },
+                    Err(error) => self.error = Some(error),
                 }
             }
         }
-        else if input == " " {
-            return;
+        else if input == "±" {
+            self.toggle_sign();
+            self.update_display();
+        }
+        else if input == "sci" && self.calculator.backend() == Backend::Programmer {
+            self.page = ButtonPage::Programmer;
+        }
+        else if input == "sci" && self.calculator.backend() == Backend::Complex {
+            self.page = ButtonPage::Complex;
+        }
+        else if input == "sci" {
+            self.page = ButtonPage::Scientific;
+        }
+        else if input == "123" {
+            self.page = ButtonPage::Basic;
+        }
+        else if input == "mat" {
+            self.page = ButtonPage::Matrix;
+        }
+        else if input == "fin" {
+            self.page = ButtonPage::Finance;
+        }
+        else if input == "angle" {
+            let angle_mode = self.calculator.angle_mode().toggled();
+            self.calculator.set_angle_mode(angle_mode);
+        }
+        else if RADIX_PREFIXES.contains(&input) {
+            if self.expects_operand() {
+                self.expression.push_str(input);
+            }
+            self.update_display();
+        }
+        else if HEX_KEYS.contains(&input) {
+            if self.radix_literal() == Some(16) {
+                self.expression.push_str(&input[1..]);
+            }
+            else if self.expects_operand() {
+                self.expression.push_str("0x");
+                self.expression.push_str(&input[1..]);
+            }
+            self.update_display();
+        }
+        else if WORD_OPERATORS.contains(&input) {
+            if self.ends_with_operand() {
+                self.expression.push_str(if input == "xor" { " xor " } else { input });
+            }
+            self.update_display();
+        }
+        else if (input.len() == 1 && self.radix_literal().is_some_and(|radix| input.chars().all(|c| c.is_digit(radix))))
+            || (input == IMAGINARY && self.ends_with_decimal()) {
+            self.expression.push_str(input);
+            self.update_display();
+        }
+        else if CONSTANTS.contains(&input) {
+            self.insert_name(input);
+        }
+        else if input.starts_with(|c: char| c.is_alphabetic()) {
+            if self.expects_operand() {
+                self.expression.push_str(input);
+                self.expression.push('(');
+            }
+            self.update_display();
         }
         else{
-            if self.check_input(input.chars().next().unwrap()) {
-                self.expression.push(input.chars().next().unwrap());
+            let input = input.chars().next().unwrap();
+            if self.check_input(input) {
+                self.expression.push(input);
             }
-            else {
-                if !self.expression.is_empty() {
-                    self.expression.pop();
-                    self.expression.push(input.chars().next().unwrap());
-                }
+            else if "+-/*^∠".contains(input) && self.expression.ends_with(|c: char| "+-/*^∠".contains(c)) {
+                self.expression.pop();
+                self.expression.push(input);
             }
             self.update_display();
         }
 
     }
 
+    fn handle_rpn(&mut self, input: &str) -> bool {
+        let entry_empty = self.expression.trim().is_empty();
+        let outcome = if input == "enter" || input == "=" {
+            if entry_empty { self.stack.command("dup") } else { self.push_entry() }
+        }
+        else if STACK_COMMANDS.contains(&input) {
+            self.push_entry().and_then(|_| self.stack.command(input))
+        }
+        else if RPN_OPERATORS.contains(&input) {
+            self.push_entry().and_then(|_| self.stack.operate(&self.calculator, input))
+        }
+        else if let Some(arity) = self.calculator.function_arity(input) {
+            match arity {
+                Arity::Exact(count) => self.push_entry().and_then(|_| self.stack.call(&self.calculator, input, arity, count)),
+                _ => self.entry_count().and_then(|count| {
+                    let count = count.unwrap_or(self.stack.depth());
+                    self.stack.call(&self.calculator, input, arity, count)
+                }),
+            }
+        }
+        else if input == "±" && entry_empty {
+            self.stack.negate(&self.calculator)
+        }
+        else if input == "<-" && entry_empty {
+            self.stack.command("drop")
+        }
+        else if input == "C" && entry_empty {
+            self.stack.clear();
+            Ok(())
+        }
+        else {
+            return false;
+        };
+        if let Err(error) = outcome {
+            self.error = Some(error);
+        }
+        true
+    }
+
+    fn handle_memory(&mut self, input: &str) {
+        if input == "MC" {
+            self.memory.clear_register();
+        }
+        else if input == "MR" {
+            if let Some(value) = self.memory.register().cloned() {
+                self.recall_value(value);
+            }
+        }
+        else {
+            let outcome = self.current_value()
+                .and_then(|value| value.map_or(Ok(()), |value| self.memory.accumulate(&self.calculator, &input[1..], &value)));
+            if let Err(error) = outcome {
+                self.error = Some(error);
+            }
+        }
+    }
+
+    fn current_value(&self) -> Result<Option<Value>, CalcError> {
+        if self.expression.trim().is_empty() || self.result.is_some() {
+            return Ok(self.shown_value().cloned());
+        }
+        self.calculator.evaluate_expression(&self.expression).map(Some)
+    }
+
+    fn entry_count(&mut self) -> Result<Option<usize>, CalcError> {
+        if self.expression.trim().is_empty() {
+            return Ok(None);
+        }
+        let count = self.calculator.evaluate_expression(&self.expression)?.to_f64();
+        if count < 0.0 || count.fract() != 0.0 {
+            let message = String::from("The argument count must be a whole number");
+            return Err(CalcError::Domain { message, span: Span { start: 0, end: self.expression.len() } });
+        }
+        self.expression.clear();
+        Ok(Some(count as usize))
+    }
+
+    fn push_entry(&mut self) -> Result<(), CalcError> {
+        if self.expression.trim().is_empty() {
+            return Ok(());
+        }
+        let value = self.calculator.evaluate_expression(&self.expression)?;
+        self.stack.push(value);
+        self.expression.clear();
+        Ok(())
+    }
+
+    fn shown_value(&self) -> Option<&Value> {
+        if self.rpn { self.stack.x() } else { self.result.as_ref() }
+    }
+
+    fn backspace(&mut self) {
+        if let Some(token) = MULTI_CHAR_TOKENS.iter().find(|token| self.expression.ends_with(*token)) {
+            self.expression.truncate(self.expression.len() - token.len());
+            return;
+        }
+        if self.radix_literal().is_some() {
+            self.expression.pop();
+            return;
+        }
+        match self.expression.pop() {
+            Some('(') => {
+                let name_start = self.expression.trim_end_matches(|c: char| c.is_ascii_alphanumeric()).len();
+                if self.expression[name_start..].starts_with(|c: char| c.is_alphabetic()) {
+                    self.expression.truncate(name_start);
+                }
+            }
+            Some(popped) if popped.is_alphabetic() => {
+                let name_start = self.expression.trim_end_matches(|c: char| c.is_alphabetic()).len();
+                self.expression.truncate(name_start);
+            }
+            _ => {}
+        }
+        let trimmed = self.expression.trim_end().len();
+        self.expression.truncate(trimmed);
+    }
+
     fn check_input(&mut self, input: char) -> bool {
-        if "+-/*^".contains(input) {
-            if self.expression.is_empty() || !self.expression.chars().last().unwrap().is_digit(10) {
+        if input == '-' && (self.expression.is_empty() || self.expression.ends_with(|c: char| "*/^([,;=&|~<>∠ ".contains(c))) {
+            return true;
+        }
+        if "*/".contains(input) && self.expression.ends_with('.') {
+            return true;
+        }
+        if "+-/*^!&|∠°%".contains(input) && !self.ends_with_operand() {
+            return false;
+        }
+        if input == '~' && !self.expects_operand() {
+            return false;
+        }
+        if let Some(radix) = self.radix_literal() {
+            if input == '.' || (input.is_ascii_digit() && !input.is_digit(radix)) {
                 return false;
             }
         }
-        if input == '.' && (self.expression.is_empty() || !self.expression.chars().last().unwrap().is_digit(10)){
+        if input == '.' && !self.expression.ends_with(|c: char| c.is_ascii_digit() || c == ']') && !self.ends_with_name() {
+            return false;
+        }
+        if input == '[' && !self.expects_operand() {
+            return false;
+        }
+        if input.is_ascii_digit() && self.expression.ends_with([')', ']', '!', '°', '%']) {
             return false;
         }
+        if input == '(' && !self.expects_operand() && !self.ends_with_name() {
+            return false;
+        }
+        if input == ')' || input == ',' {
+            let open = self.expression.matches(['(', '[']).count();
+            let closed = self.expression.matches([')', ']']).count();
+            if open <= closed || !self.ends_with_operand() {
+                return false;
+            }
+        }
+        if input == ']' || input == ';' {
+            let open = self.expression.matches('[').count();
+            let closed = self.expression.matches(']').count();
+            if open <= closed || !self.ends_with_operand() {
+                return false;
+            }
+        }
         true
     }
 
+    fn ends_with_operand(&self) -> bool {
+        self.expression.trim_end().ends_with(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == ')' || c == ']' || c == '!' || c == '°' || c == '%')
+    }
+
+    fn ends_with_decimal(&self) -> bool {
+        self.expression.ends_with(|c: char| c.is_ascii_digit()) && self.radix_literal().is_none()
+    }
+
+    fn accepts_exponent(&self) -> bool {
+        let mantissa_start = self.expression.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.').len();
+        let before_mantissa = &self.expression[..mantissa_start];
+        self.ends_with_decimal()
+            && !before_mantissa.ends_with(|c: char| c.is_alphanumeric() || c == '_')
+            && !["e-", "e+", "E-", "E+"].iter().any(|sign| before_mantissa.ends_with(sign))
+    }
+
+    fn ends_with_name(&self) -> bool {
+        let name_start = self.expression.trim_end_matches(|c: char| c.is_alphanumeric() || c == '_').len();
+        self.expression[name_start..].starts_with(|c: char| c.is_alphabetic())
+    }
+
+    fn expects_operand(&self) -> bool {
+        self.expression.is_empty()
+            || self.expression.ends_with(|c: char| "+-*/^([,;=&|~<>∠".contains(c))
+            || self.expression.ends_with(" xor ")
+            || self.expression.ends_with(" in ")
+    }
+
+    fn radix_literal(&self) -> Option<u32> {
+        let literal_start = self.expression.trim_end_matches(|c: char| c.is_ascii_alphanumeric()).len();
+        match self.expression[literal_start..].get(..2) {
+            Some("0x") => Some(16),
+            Some("0o") => Some(8),
+            Some("0b") => Some(2),
+            _ => None,
+        }
+    }
+
+    fn accepts_radix_prefix(&self) -> bool {
+        let number_start = self.expression.trim_end_matches(|c: char| c.is_ascii_alphanumeric()).len();
+        let before_number = &self.expression[..number_start];
+        &self.expression[number_start..] == "0"
+            && (before_number.is_empty() || before_number.ends_with(|c: char| "+-*/^(,=&|~<> ".contains(c)))
+    }
+
+    fn handle_event(&mut self, ctx: &egui::Context, event: Event) {
+        match event {
+            Event::Text(text) => {
+                for input in text.chars() {
+                    self.handle_typed(input);
+                }
+            }
+            Event::Key { key, pressed: true, .. } => match key {
+                Key::Enter => self.handle_input("="),
+                Key::Backspace => self.handle_input("<-"),
+                Key::Escape => self.handle_input("C"),
+                _ => {}
+            },
+            Event::Copy => {
+                let text = match self.shown_value() {
+                    Some(result) => result.format(&self.display_options),
+                    None => self.expression.clone(),
+                };
+                if !text.is_empty() {
+                    ctx.copy_text(text);
+                }
+            }
+            Event::Paste(text) => self.paste(&text),
+            _ => {}
+        }
+    }
+
+    fn handle_typed(&mut self, input: char) {
+        if input.is_ascii_digit() || "+-*/^()[].,;!&|~∠°%".contains(input) {
+            self.handle_input(&input.to_string());
+        }
+        else if input == '<' || input == '>' {
+            self.error = None;
+            self.result = None;
+            let pair = format!("{}{}", input, input);
+            let pending = self.expression.ends_with(input) && !self.expression.ends_with(&pair);
+            if pending || self.ends_with_operand() {
+                self.expression.push(input);
+            }
+            self.update_display();
+        }
+        else if input.is_alphabetic() || input == '_' {
+            self.error = None;
+            let showing_result = self.result.take().is_some();
+            let radix_digit = self.radix_literal().is_some_and(|radix| input.is_digit(radix));
+            let radix_prefix = "xob".contains(input.to_ascii_lowercase()) && self.accepts_radix_prefix();
+            let exponent = (input == 'e' || input == 'E') && !showing_result && self.accepts_exponent();
+            if radix_digit || radix_prefix || exponent {
+                self.expression.push(input);
+            }
+            else if let Some(text) = self.typed_xor(input) {
+                self.expression.push_str(text);
+            }
+            else if showing_result {
+                self.expression = input.to_string();
+            }
+            else if self.expects_operand() || self.ends_with_name() || self.expression.ends_with(' ') {
+                self.expression.push(input);
+            }
+            else if self.ends_with_operand() {
+                self.expression.push(' ');
+                self.expression.push(input);
+            }
+            self.update_display();
+        }
+        else if input == ' ' {
+            if self.ends_with_name() {
+                self.expression.push(' ');
+            }
+            self.update_display();
+        }
+        else if input == '=' {
+            self.error = None;
+            self.result = None;
+            let nested = self.expression.matches('(').count() > self.expression.matches(')').count();
+            if self.ends_with_operand() && (nested || !self.expression.contains('=')) {
+                self.expression.push('=');
+            }
+            self.update_display();
+        }
+    }
+
+    fn typed_xor(&self, input: char) -> Option<&'static str> {
+        if input == 'x' && self.ends_with_operand() && !self.ends_with_name() && self.radix_literal().is_none() {
+            Some(" x")
+        }
+        else if input == 'o' && self.expression.ends_with(" x") {
+            Some("o")
+        }
+        else if input == 'r' && self.expression.ends_with(" xo") {
+            Some("r ")
+        }
+        else {
+            None
+        }
+    }
+
+    fn paste(&mut self, text: &str) {
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        if let Err(error) = self.calculator.check(text) {
+            self.expression = text.to_string();
+            self.update_display();
+            self.result = None;
+            self.error = Some(error);
+            return;
+        }
+        if self.result.is_none() && !self.expects_operand() {
+            self.expression.clear();
+        }
+        self.recall(text);
+    }
+
+    fn toggle_sign(&mut self) {
+        if self.expression.ends_with([')', ']', '!', '°', '%']) {
+            return;
+        }
+        let number_start = self.expression
+            .trim_end_matches(|c: char| c.is_ascii_alphanumeric() || c == '.')
+            .len();
+        let before_number = &self.expression[..number_start];
+        let sign_position = number_start.saturating_sub(1);
+        let is_unary_sign = before_number.ends_with('-')
+            && (sign_position == 0 || self.expression[..sign_position].ends_with(|c: char| "+-*/^(".contains(c)));
+        if is_unary_sign {
+            self.expression.remove(sign_position);
+        }
+        else if before_number.ends_with('-') {
+            self.expression.replace_range(sign_position..number_start, "+");
+        }
+        else if before_number.ends_with('+') {
+            self.expression.replace_range(sign_position..number_start, "-");
+        }
+        else {
+            self.expression.insert(number_start, '-');
+        }
+    }
+
+    fn insert_name(&mut self, name: &str) {
+        if self.expects_operand() {
+            self.expression.push_str(name);
+        }
+        self.update_display();
+    }
+
+    fn store_variable(&mut self) {
+        let name = self.variable_name.trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        let value = if self.result.is_some() { "ans" } else { self.expression.as_str() };
+        if value.is_empty() {
+            return;
+        }
+        if self.submit(format!("{} = {}", name, value)) {
+            self.variable_name.clear();
+        }
+    }
+
+    fn store_slot(&mut self) {
+        let name = self.slot_name.trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        match self.current_value() {
+            Ok(Some(value)) => {
+                self.memory.store(&name, &value);
+                self.slot_name.clear();
+            }
+            Ok(None) => {}
+            Err(error) => self.error = Some(error),
+        }
+    }
+
+    fn define_function(&mut self) {
+        if !self.definition.trim().is_empty() && self.submit(self.definition.clone()) {
+            self.definition.clear();
+        }
+    }
+
+    fn submit(&mut self, statement: String) -> bool {
+        self.expression = statement;
+        self.update_display();
+        self.handle_input("=");
+        self.error.is_none()
+    }
+
+    fn variables_panel(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Variables");
+        let mut inserted = None;
+        let mut removed = None;
+        Grid::new("variables_grid").num_columns(3).striped(true).show(ui, |ui| {
+            for (name, value) in self.calculator.variables() {
+                if ui.button(name).on_hover_text("Insert").clicked() {
+                    inserted = Some(name.clone());
+                }
+                ui.add(Label::new(value.format(&self.display_options)).truncate());
+                if ui.small_button("x").on_hover_text("Delete").clicked() {
+                    removed = Some(name.clone());
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(name) = inserted {
+            self.insert_name(&name);
+        }
+        if let Some(name) = removed {
+            self.calculator.remove_variable(&name);
+        }
+        ui.horizontal(|ui| {
+            ui.add(TextEdit::singleline(&mut self.variable_name).hint_text("name").desired_width(90.0));
+            if ui.button("store").clicked() {
+                self.store_variable();
+            }
+        });
+        ui.separator();
+        ui.heading("Functions");
+        let mut called = None;
+        let mut removed = None;
+        Grid::new("functions_grid").num_columns(2).striped(true).show(ui, |ui| {
+            for (name, function) in self.calculator.user_functions() {
+                if ui.add(Button::new(&function.source).truncate()).on_hover_text("Insert").clicked() {
+                    called = Some(name.clone());
+                }
+                if ui.small_button("x").on_hover_text("Delete").clicked() {
+                    removed = Some(name.clone());
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(name) = called {
+            if self.expects_operand() {
+                self.expression.push_str(&name);
+                self.expression.push('(');
+            }
+            self.update_display();
+        }
+        if let Some(name) = removed {
+            self.calculator.remove_user_function(&name);
+        }
+        ui.horizontal(|ui| {
+            ui.add(TextEdit::singleline(&mut self.definition).hint_text("f(x) = x^2").desired_width(90.0));
+            if ui.button("define").clicked() {
+                self.define_function();
+            }
+        });
+    }
+
+    fn memory_panel(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Memory");
+        let mut recalled = None;
+        let mut removed = None;
+        Grid::new("memory_grid").num_columns(3).striped(true).show(ui, |ui| {
+            if let Some(value) = self.memory.register() {
+                if ui.button("M").on_hover_text("Insert").clicked() {
+                    recalled = Some(value.clone());
+                }
+                ui.add(Label::new(value.format(&self.display_options)).truncate());
+                ui.end_row();
+            }
+            for (name, value) in self.memory.slots() {
+                if ui.button(name).on_hover_text("Insert").clicked() {
+                    recalled = Some(value.clone());
+                }
+                ui.add(Label::new(value.format(&self.display_options)).truncate());
+                if ui.small_button("x").on_hover_text("Delete").clicked() {
+                    removed = Some(name.clone());
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(value) = recalled {
+            self.recall_value(value);
+        }
+        if let Some(name) = removed {
+            self.memory.remove(&name);
+        }
+        ui.horizontal(|ui| {
+            ui.add(TextEdit::singleline(&mut self.slot_name).hint_text("slot").desired_width(90.0));
+            if ui.button("keep").clicked() {
+                self.store_slot();
+            }
+        });
+    }
+
+    fn recall_value(&mut self, value: Value) {
+        if self.rpn && self.expression.trim().is_empty() {
+            self.stack.push(value);
+        }
+        else if self.expression.trim().is_empty() || self.result.is_some() {
+            self.error = None;
+            self.expression = self.operand_text(&value);
+            self.result = Some(value);
+            self.update_display();
+        }
+        else {
+            self.recall(&self.operand_text(&value));
+        }
+    }
+
+    fn recall(&mut self, text: &str) {
+        if self.result.take().is_some() {
+            self.expression.clear();
+        }
+        self.error = None;
+        if self.expression.is_empty() || text.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_') {
+            self.insert_name(text);
+        }
+        else {
+            self.insert_name(&format!("({})", text));
+        }
+    }
+
+    fn history_panel(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.heading("History");
+            if ui.add_enabled(!self.history.is_empty(), Button::new("clear")).clicked() {
+                self.history.clear();
+            }
+        });
+        ui.separator();
+        let mut recalled = None;
+        ScrollArea::vertical().stick_to_bottom(true).show(ui, |ui| {
+            for entry in self.history.entries() {
+                ui.horizontal_wrapped(|ui| {
+                    if ui.link(&entry.expression).on_hover_text("Insert expression").clicked() {
+                        recalled = Some(entry.expression.clone());
+                    }
+                    ui.label("=");
+                    if ui.link(&entry.result).on_hover_text("Insert result").clicked() {
+                        recalled = Some(entry.result.clone());
+                    }
+                });
+            }
+        });
+        if let Some(text) = recalled {
+            self.recall(&text);
+        }
+    }
+
+    fn bases_grid(&self, ui: &mut egui::Ui) {
+        Grid::new("bases_grid").num_columns(2).show(ui, |ui| {
+            for (label, radix) in BASES {
+                let text = match self.shown_value() {
+                    Some(Value::Integer { value, size }) if radix == 2 => group_digits(&size.format(*value, radix), 4),
+                    Some(Value::Integer { value, size }) => size.format(*value, radix),
+                    _ => String::new(),
+                };
+                ui.label(RichText::new(label).small());
+                ui.add(Label::new(RichText::new(text).monospace().small()).wrap());
+                ui.end_row();
+            }
+        });
+    }
+
+    fn operand_text(&self, result: &Value) -> String {
+        let text = result.format(&self.display_options);
+        match result {
+            Value::Complex(_) if text.contains([' ', '∠']) => format!("({})", text),
+            _ => text,
+        }
+    }
+
+    fn matrix_grid(&self, ui: &mut egui::Ui) {
+        let Some(Value::Matrix { rows, columns, elements }) = self.shown_value() else {
+            return;
+        };
+        ScrollArea::both().max_height(MATRIX_GRID_HEIGHT).show(ui, |ui| {
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                Grid::new("matrix_grid").num_columns(*columns).spacing(Vec2::new(16.0, 4.0)).show(ui, |ui| {
+                    for row in 0..*rows {
+                        for element in &elements[row * columns..(row + 1) * columns] {
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                ui.label(RichText::new(element.format(&self.display_options)).monospace());
+                            });
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+    }
+
     fn update_display(&mut self) {
         self.display = self.expression.clone();
     }
 
+    fn display_layout(&self, text: &str, font_size: f32) -> LayoutJob {
+        let normal = TextFormat::simple(FontId::monospace(font_size), Color32::GRAY);
+        let mut job = LayoutJob::default();
+        let Some(error) = self.error.as_ref().filter(|_| !text.is_empty()) else {
+            job.append(text, 0.0, normal);
+            return job;
+        };
+        let marked = TextFormat {
+            color: Color32::RED,
+            underline: Stroke::new(2.0, Color32::RED),
+            ..normal.clone()
+        };
+        let span = error.span();
+        let start = span.start.min(text.len());
+        let end = span.end.clamp(start, text.len());
+        job.append(&text[..start], 0.0, normal.clone());
+        if start == end {
+            job.append("^", 0.0, marked);
+        }
+        else {
+            job.append(&text[start..end], 0.0, marked);
+        }
+        job.append(&text[end..], 0.0, normal);
+        job
+    }
+
     fn clear(&mut self) {
         self.display.clear();
-        self.current_input.clear();
-        self.operator = None;
         self.result = None;
+        self.error = None;
         self.expression.clear();
     }
 }
 
 impl App for CalculatorApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut Frame) {
+        if !ctx.wants_keyboard_input() {
+            let events = ctx.input(|input| input.events.clone());
+            for event in events {
+                self.handle_event(ctx, event);
+            }
+        }
+        SidePanel::left("history").exact_width(HISTORY_PANEL_WIDTH).show(ctx, |ui| {
+            self.history_panel(ui);
+        });
+        SidePanel::right("variables").exact_width(VARIABLES_PANEL_WIDTH).show(ctx, |ui| {
+            self.variables_panel(ui);
+            ui.separator();
+            self.memory_panel(ui);
+        });
+        egui::Window::new("Plot").open(&mut self.plot_open).default_size([520.0, 420.0]).show(ctx, |ui| {
+            self.plotter.show(ui, &self.calculator);
+        });
+        egui::Window::new("Data").open(&mut self.data_open).default_size([420.0, 480.0]).show(ctx, |ui| {
+            self.data_panel.show(ui, &mut self.calculator, &self.display_options);
+        });
+        egui::Window::new("Loan").open(&mut self.loan_open).default_size([480.0, 480.0]).show(ctx, |ui| {
+            self.loan_panel.show(ui, &self.calculator, &self.display_options);
+        });
         CentralPanel::default().show(ctx, |ui| {
             ui.vertical(|ui| {
-                let buttons = [
-                    ["7", "8", "9", "+"],
-                    ["4", "5", "6", "-"],
-                    ["1", "2", "3", "*"],
-                    ["0", ".", "=", "/"],
-                    ["C", "<-", " ", "^"]
-                ];
+                let buttons: &[[&str; 4]] = match self.page {
+                    ButtonPage::Basic if self.rpn => &RPN_BUTTONS,
+                    ButtonPage::Basic => &BASIC_BUTTONS,
+                    ButtonPage::Scientific => &SCIENTIFIC_BUTTONS,
+                    ButtonPage::Programmer => &PROGRAMMER_BUTTONS,
+                    ButtonPage::Complex => &COMPLEX_BUTTONS,
+                    ButtonPage::Matrix => &MATRIX_BUTTONS,
+                    ButtonPage::Finance => &FINANCE_BUTTONS,
+                };
 
                 let font_size = 20.0;
 
-                let rows = buttons.len() as f32;
+                let rows = buttons.len() as f32 + 1.0;
                 let columns = buttons[0].len() as f32;
 
+                ui.horizontal(|ui| {
+                    for backend in Backend::ALL {
+                        if ui.selectable_label(self.calculator.backend() == backend, backend.label()).clicked() {
+                            self.calculator.set_backend(backend);
+                            if matches!(self.page, ButtonPage::Programmer | ButtonPage::Complex) {
+                                self.page = ButtonPage::Basic;
+                            }
+                        }
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.toggle_value(&mut self.plot_open, "plot");
+                        ui.toggle_value(&mut self.data_open, "data");
+                        ui.toggle_value(&mut self.loan_open, "loan");
+                        ui.toggle_value(&mut self.rpn, "RPN");
+                    });
+                });
+
+                if let Some(notice) = &self.notice {
+                    let mut dismissed = false;
+                    ui.horizontal(|ui| {
+                        dismissed = ui.small_button("x").on_hover_text("Dismiss").clicked();
+                        ui.add(Label::new(RichText::new(notice).small().color(Color32::RED)).wrap());
+                    });
+                    if dismissed {
+                        self.notice = None;
+                    }
+                }
+
+                if self.calculator.backend() == Backend::Programmer {
+                    let mut word_size = self.calculator.word_size();
+                    ui.horizontal(|ui| {
+                        for bits in WordSize::BITS {
+                            ui.selectable_value(&mut word_size.bits, bits, bits.to_string());
+                        }
+                        ui.checkbox(&mut word_size.signed, "signed");
+                    });
+                    if word_size != self.calculator.word_size() {
+                        self.calculator.set_word_size(word_size);
+                    }
+                }
+
+                let previous_options = self.display_options;
+                if self.calculator.backend() == Backend::Rational {
+                    ui.horizontal(|ui| {
+                        ui.selectable_value(&mut self.display_options.rational, RationalDisplay::Fraction, "a/b");
+                        ui.selectable_value(&mut self.display_options.rational, RationalDisplay::Decimal, "0.x");
+                        ui.add(DragValue::new(&mut self.display_options.decimal_digits).range(0..=100).suffix(" digits"));
+                    });
+                }
+                if self.calculator.backend() == Backend::Complex {
+                    ui.horizontal(|ui| {
+                        ui.selectable_value(&mut self.display_options.complex, ComplexDisplay::Rectangular, "a+bi");
+                        ui.selectable_value(&mut self.display_options.complex, ComplexDisplay::Polar, "r∠θ");
+                    });
+                }
+                if self.display_options != previous_options {
+                    if let Some(result) = &self.result {
+                        self.expression = self.operand_text(result);
+                        self.update_display();
+                    }
+                }
+
+                if self.rpn {
+                    self.stack.show(ui, &self.display_options);
+                }
+
+                let mut layouter = |ui: &egui::Ui, text: &str, _wrap_width: f32| {
+                    let job = self.display_layout(text, font_size);
+                    ui.fonts(|fonts| fonts.layout_job(job))
+                };
+                let mut display = self.display.clone();
                 ui.add(TextEdit::singleline(
-                    &mut self.display).font(
-                    egui::FontId::monospace(font_size)).interactive(false).desired_width(WINDOW_WIDTH));
+                    &mut display).font(
+                    egui::FontId::monospace(font_size)).interactive(false).desired_width(ui.available_width())
+                    .layouter(&mut layouter));
+
+                let message = match &self.error {
+                    Some(error) if self.expression.is_empty() => error.message(),
+                    Some(error) => error.to_string(),
+                    None => String::new(),
+                };
+                ui.add_sized(Vec2::new(ui.available_width(), ERROR_FONT_SIZE),
+                    Label::new(RichText::new(message).size(ERROR_FONT_SIZE).color(Color32::RED)).truncate());
+
+                if self.calculator.backend() == Backend::Programmer {
+                    self.bases_grid(ui);
+                }
+                self.matrix_grid(ui);
+                if !self.rpn {
+                    self.steps.show(ui, &self.display_options);
+                }
+
+                let available = ui.available_size();
+                let button_size = Vec2::new(
+                    (available.x - 10.0 * (columns - 1.0)) / columns,
+                    (available.y - 10.0 * (rows - 1.0)) / rows);
 
                 Grid::new("calculator_grid").num_columns(columns as usize).spacing(Vec2::new(10.0, 10.0)).show(ui, |ui| {
-                    let button_size = Vec2::new(
-                        (WINDOW_WIDTH - 5.0 - 10.0 * columns) / columns,
-                        (WINDOW_HEIGHT - 5.0 - font_size - 10.0 * rows) / rows);
 
-                    for row in buttons{
-                        for input in row {
-                            if ui.add_sized(button_size, Button::new(input.to_string())).clicked() {
+                    for row in std::iter::once(&MEMORY_BUTTONS).chain(buttons) {
+                        for &input in row {
+                            let label = match input {
+                                "angle" => self.calculator.angle_mode().label(),
+                                "sci" if self.calculator.backend() == Backend::Programmer => "hex",
+                                "sci" if self.calculator.backend() == Backend::Complex => "cplx",
+                                _ if HEX_KEYS.contains(&input) => &input[1..],
+                                _ => input,
+                            };
+                            if ui.add_sized(button_size, Button::new(label)).clicked() {
                                 self.handle_input(input);
                             }
                         }
@@ -124,14 +1078,30 @@ impl App for CalculatorApp {
             });
         });
     }
+
+    fn save(&mut self, storage: &mut dyn Storage) {
+        eframe::set_value(storage, History::STORAGE_KEY, &self.history);
+        eframe::set_value(storage, Memory::STORAGE_KEY, &self.memory.saved());
+    }
+}
+
+fn group_digits(digits: &str, size: usize) -> String {
+    let first = digits.len() % size;
+    let mut grouped = String::from(&digits[..first]);
+    for (index, digit) in digits[first..].chars().enumerate() {
+        if index % size == 0 && !grouped.is_empty() {
+            grouped.push(' ');
+        }
+        grouped.push(digit);
+    }
+    grouped
 }
 
 fn main() {
-    let app = CalculatorApp::default();
     let native_options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default().with_inner_size([WINDOW_WIDTH, WINDOW_HEIGHT]),
+        viewport: egui::ViewportBuilder::default().with_inner_size([HISTORY_PANEL_WIDTH + WINDOW_WIDTH + VARIABLES_PANEL_WIDTH, WINDOW_HEIGHT]),
         ..Default::default()
     };
-    eframe::run_native("Calculator", native_options, Box::new(|_cc|
-        Ok(Box::new(app)))).expect("Помилка");
+    eframe::run_native(APP_NAME, native_options, Box::new(|cc|
+        Ok(Box::new(CalculatorApp::new(cc))))).expect("Помилка");
 }
\ No newline at end of file
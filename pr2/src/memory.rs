@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use calc_core::{Backend, CalcError, Calculator, Value, WordSize};
+
+const EXPRESSION_TAG: &str = "expression";
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Memory {
+    register: Option<Value>,
+    slots: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct SavedMemory {
+    register: Option<StoredValue>,
+    slots: BTreeMap<String, StoredValue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredValue {
+    backend: String,
+    bits: u32,
+    signed: bool,
+    text: String,
+}
+
+impl StoredValue {
+    fn new(value: &Value) -> Self {
+        let size = word_size(value).unwrap_or_default();
+        let backend = backend(value).map_or(EXPRESSION_TAG, Backend::label);
+        StoredValue { backend: backend.to_string(), bits: size.bits, signed: size.signed, text: exact_text(value) }
+    }
+
+    fn restore(&self, calculator: &mut Calculator) -> Option<Value> {
+        if self.backend == EXPRESSION_TAG {
+            return Some(Value::Expression(self.text.clone()));
+        }
+        let backend = Backend::ALL.into_iter().find(|backend| backend.label() == self.backend)?;
+        let (previous_backend, previous_size) = (calculator.backend(), calculator.word_size());
+        calculator.set_backend(backend);
+        calculator.set_word_size(WordSize { bits: self.bits, signed: self.signed });
+        let value = calculator.evaluate_expression(&self.text).ok();
+        calculator.set_backend(previous_backend);
+        calculator.set_word_size(previous_size);
+        value
+    }
+}
+
+impl Memory {
+    pub(crate) const STORAGE_KEY: &'static str = "memory";
+
+    pub(crate) fn restore(saved: &SavedMemory, calculator: &mut Calculator) -> Self {
+        Memory {
+            register: saved.register.as_ref().and_then(|stored| stored.restore(calculator)),
+            slots: saved.slots.iter()
+                .filter_map(|(name, stored)| Some((name.clone(), stored.restore(calculator)?)))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn saved(&self) -> SavedMemory {
+        SavedMemory {
+            register: self.register.as_ref().map(StoredValue::new),
+            slots: self.slots.iter().map(|(name, value)| (name.clone(), StoredValue::new(value))).collect(),
+        }
+    }
+
+    pub(crate) fn register(&self) -> Option<&Value> {
+        self.register.as_ref()
+    }
+
+    pub(crate) fn accumulate(&mut self, calculator: &Calculator, operator: &str, value: &Value) -> Result<(), CalcError> {
+        let total = match &self.register {
+            Some(register) => calculator.apply_operator(operator, register, value)?,
+            None if operator == "-" => calculator.negate(value)?,
+            None => value.clone(),
+        };
+        self.register = Some(total);
+        Ok(())
+    }
+
+    pub(crate) fn clear_register(&mut self) {
+        self.register = None;
+    }
+
+    pub(crate) fn slots(&self) -> &BTreeMap<String, Value> {
+        &self.slots
+    }
+
+    pub(crate) fn store(&mut self, name: &str, value: &Value) {
+        self.slots.insert(name.to_string(), value.clone());
+    }
+
+    pub(crate) fn remove(&mut self, name: &str) {
+        self.slots.remove(name);
+    }
+}
+
+fn backend(value: &Value) -> Option<Backend> {
+    match value {
+        Value::Float(_) => Some(Backend::Float),
+        Value::Decimal(_) => Some(Backend::Decimal),
+        Value::Rational(_) => Some(Backend::Rational),
+        Value::Integer { .. } => Some(Backend::Programmer),
+        Value::Complex(_) => Some(Backend::Complex),
+        Value::Quantity(quantity) => backend(&quantity.magnitude),
+        Value::Matrix { elements, .. } => elements.first().and_then(backend),
+        Value::Expression(_) => None,
+    }
+}
+
+fn word_size(value: &Value) -> Option<WordSize> {
+    match value {
+        Value::Integer { size, .. } => Some(*size),
+        Value::Matrix { elements, .. } => elements.first().and_then(word_size),
+        _ => None,
+    }
+}
+
+fn exact_text(value: &Value) -> String {
+    match value {
+        Value::Float(value) => value.to_string(),
+        Value::Decimal(value) => value.to_string(),
+        Value::Rational(value) => value.to_string(),
+        Value::Integer { value, .. } => value.to_string(),
+        Value::Complex(value) => format!("{} + ({}) * i", value.re, value.im),
+        Value::Quantity(quantity) => format!("(({}) {}) in {}", exact_text(&quantity.magnitude), quantity.unit, quantity.unit),
+        Value::Matrix { columns, elements, .. } => {
+            let rows = elements.chunks(*columns)
+                .map(|row| row.iter().map(exact_text).collect::<Vec<_>>().join(", "))
+                .collect::<Vec<_>>();
+            format!("[{}]", rows.join("; "))
+        }
+        Value::Expression(expression) => expression.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use calc_core::{Backend, Calculator};
+
+    use super::Memory;
+
+    #[test]
+    fn adds_exact_rationals() {
+        let mut calculator = Calculator::default();
+        calculator.set_backend(Backend::Rational);
+        let third = calculator.evaluate_expression("1/3").unwrap();
+        let mut memory = Memory::default();
+        for _ in 0..3 {
+            memory.accumulate(&calculator, "+", &third).unwrap();
+        }
+        assert_eq!(memory.register(), Some(&calculator.evaluate_expression("1").unwrap()));
+    }
+
+    #[test]
+    fn restores_values_in_their_own_backend() {
+        let mut calculator = Calculator::default();
+        let mut memory = Memory::default();
+        for (backend, expression) in [
+            (Backend::Rational, "1/3"),
+            (Backend::Decimal, "0.1234567890123456789012345"),
+            (Backend::Float, "1/3"),
+            (Backend::Complex, "1/3 - 2i"),
+            (Backend::Programmer, "0xFF"),
+            (Backend::Rational, "(1/3 km) in mi"),
+            (Backend::Rational, "[1/3, 2; 3, 1/7]"),
+        ] {
+            calculator.set_backend(backend);
+            let value = calculator.evaluate_expression(expression).unwrap();
+            memory.store(expression, &value);
+        }
+        memory.store("symbolic", &calculator.calculate("simplify(x + x)").unwrap().unwrap());
+        calculator.set_backend(Backend::Float);
+        let restored = Memory::restore(&memory.saved(), &mut calculator);
+        assert_eq!(restored.slots(), memory.slots());
+        assert_eq!(calculator.backend(), Backend::Float);
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::BTreeMap;
+
+use eframe::egui::{Color32, Key, Label, RichText, TextEdit, Ui};
+use egui_plot::{Legend, Line, MarkerShape, Plot, PlotBounds, PlotPoints, Points, VLine};
+
+use calc_core::{AngleMode, CalcError, Calculator, UserFunction, Value};
+
+const VARIABLE: &str = "x";
+const DEFAULT_RANGE: f64 = 10.0;
+const SAMPLES: usize = 600;
+const REFINE_STEPS: usize = 60;
+const MARKER_RADIUS: f32 = 4.0;
+const COLORS: [Color32; 6] = [
+    Color32::from_rgb(66, 133, 244),
+    Color32::from_rgb(219, 68, 55),
+    Color32::from_rgb(15, 157, 88),
+    Color32::from_rgb(244, 160, 0),
+    Color32::from_rgb(171, 71, 188),
+    Color32::from_rgb(0, 172, 193),
+];
+
+struct Curve {
+    function: UserFunction,
+    color: Color32,
+    sampled: Option<Sampled>,
+}
+
+struct Sampled {
+    bounds: [f64; 4],
+    angle_mode: AngleMode,
+    variables: BTreeMap<String, Value>,
+    functions: BTreeMap<String, UserFunction>,
+    features: Features,
+}
+
+#[derive(Default)]
+struct Features {
+    segments: Vec<Vec<[f64; 2]>>,
+    roots: Vec<[f64; 2]>,
+    extrema: Vec<[f64; 2]>,
+}
+
+#[derive(Default)]
+pub(crate) struct Plotter {
+    curves: Vec<Curve>,
+    input: String,
+    error: Option<CalcError>,
+    added: usize,
+}
+
+impl Plotter {
+    pub(crate) fn show(&mut self, ui: &mut Ui, calculator: &Calculator) {
+        ui.horizontal(|ui| {
+            ui.label("f(x) =");
+            let response = ui.add(TextEdit::singleline(&mut self.input).hint_text("x^2 - 2").desired_width(200.0));
+            let submitted = response.lost_focus() && ui.input(|input| input.key_pressed(Key::Enter));
+            if ui.button("plot").clicked() || submitted {
+                self.add_curve(calculator);
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.label(RichText::new(error.to_string()).color(Color32::RED));
+        }
+        let mut removed = None;
+        ui.horizontal_wrapped(|ui| {
+            for (index, curve) in self.curves.iter().enumerate() {
+                ui.label(RichText::new(&curve.function.source).color(curve.color));
+                if ui.small_button("x").on_hover_text("Remove").clicked() {
+                    removed = Some(index);
+                }
+            }
+        });
+        if let Some(index) = removed {
+            self.curves.remove(index);
+        }
+
+        let height = ui.available_height() - ui.spacing().interact_size.y;
+        let response = Plot::new("plot")
+            .legend(Legend::default())
+            .include_x(-DEFAULT_RANGE)
+            .include_x(DEFAULT_RANGE)
+            .include_y(-DEFAULT_RANGE)
+            .include_y(DEFAULT_RANGE)
+            .auto_bounds(false.into())
+            .show_x(false)
+            .show_y(false)
+            .height(height)
+            .show(ui, |plot_ui| {
+                let bounds = plot_ui.plot_bounds();
+                for curve in &mut self.curves {
+                    curve.refresh(calculator, &bounds);
+                    let Some(Sampled { features, .. }) = &curve.sampled else {
+                        continue;
+                    };
+                    for (index, segment) in features.segments.iter().enumerate() {
+                        let line = Line::new(PlotPoints::from(segment.clone())).color(curve.color);
+                        let line = if index == 0 { line.name(&curve.function.source) } else { line };
+                        plot_ui.line(line);
+                    }
+                    plot_ui.points(Points::new(features.roots.clone())
+                        .shape(MarkerShape::Circle)
+                        .radius(MARKER_RADIUS)
+                        .color(curve.color));
+                    plot_ui.points(Points::new(features.extrema.clone())
+                        .shape(MarkerShape::Diamond)
+                        .radius(MARKER_RADIUS)
+                        .filled(false)
+                        .color(curve.color));
+                }
+                let cursor = plot_ui.pointer_coordinate().map(|point| point.x);
+                if let Some(x) = cursor {
+                    plot_ui.vline(VLine::new(x).color(Color32::GRAY));
+                    for curve in &self.curves {
+                        if let Some(y) = value_at(calculator, &curve.function, x) {
+                            plot_ui.points(Points::new(vec![[x, y]]).radius(MARKER_RADIUS).color(curve.color));
+                        }
+                    }
+                }
+                cursor
+            });
+
+        ui.horizontal(|ui| {
+            let Some(x) = response.inner else {
+                ui.label("Drag to pan, ctrl+scroll to zoom, double-click to reset");
+                return;
+            };
+            ui.label(format!("x = {:.4}", x));
+            for curve in &self.curves {
+                let value = value_at(calculator, &curve.function, x)
+                    .map(|y| format!("{:.4}", y))
+                    .unwrap_or_else(|| String::from("undefined"));
+                ui.add(Label::new(RichText::new(value).color(curve.color)).truncate());
+            }
+        });
+    }
+
+    fn add_curve(&mut self, calculator: &Calculator) {
+        self.error = None;
+        if self.input.trim().is_empty() {
+            return;
+        }
+        match calculator.compile_function(&self.input, &[VARIABLE]) {
+            Ok(function) => {
+                let color = COLORS[self.added % COLORS.len()];
+                self.curves.push(Curve { function, color, sampled: None });
+                self.added += 1;
+                self.input.clear();
+            }
+            Err(error) => self.error = Some(error),
+        }
+    }
+}
+
+impl Curve {
+    fn refresh(&mut self, calculator: &Calculator, bounds: &PlotBounds) {
+        let [min_x, min_y] = bounds.min();
+        let [max_x, max_y] = bounds.max();
+        let key = [min_x, min_y, max_x, max_y];
+        let fresh = self.sampled.as_ref().is_some_and(|sampled| {
+            sampled.bounds == key
+                && sampled.angle_mode == calculator.angle_mode()
+                && &sampled.variables == calculator.variables()
+                && &sampled.functions == calculator.user_functions()
+        });
+        if !fresh {
+            self.sampled = Some(Sampled {
+                bounds: key,
+                angle_mode: calculator.angle_mode(),
+                variables: calculator.variables().clone(),
+                functions: calculator.user_functions().clone(),
+                features: sample(calculator, &self.function, bounds),
+            });
+        }
+    }
+}
+
+fn value_at(calculator: &Calculator, function: &UserFunction, x: f64) -> Option<f64> {
+    calculator.call_function(function, vec![x]).ok().filter(|y| y.is_finite())
+}
+
+fn sample(calculator: &Calculator, function: &UserFunction, bounds: &PlotBounds) -> Features {
+    let [min_x, min_y] = bounds.min();
+    let [max_x, max_y] = bounds.max();
+    let jump = (max_y - min_y).abs();
+    let step = (max_x - min_x) / SAMPLES as f64;
+    let mut features = Features::default();
+    let mut segment: Vec<[f64; 2]> = Vec::new();
+    for index in 0..=SAMPLES {
+        let x = min_x + step * index as f64;
+        let Some(y) = value_at(calculator, function, x) else {
+            features.segments.push(std::mem::take(&mut segment));
+            continue;
+        };
+        if segment.last().is_some_and(|[_, previous]| (y - previous).abs() > jump && y.signum() != previous.signum()) {
+            features.segments.push(std::mem::take(&mut segment));
+        }
+        segment.push([x, y]);
+    }
+    features.segments.push(segment);
+    features.segments.retain(|segment| !segment.is_empty());
+
+    for segment in &features.segments {
+        for pair in segment.windows(2) {
+            let ([left_x, left_y], [right_x, right_y]) = (pair[0], pair[1]);
+            if left_y == 0.0 {
+                features.roots.push([left_x, 0.0]);
+            }
+            else if right_y != 0.0 && left_y.signum() != right_y.signum() {
+                if let Some(root) = bisect(calculator, function, left_x, right_x, left_y) {
+                    features.roots.push([root, 0.0]);
+                }
+            }
+        }
+        for triple in segment.windows(3) {
+            let rising = triple[1][1] - triple[0][1];
+            let falling = triple[2][1] - triple[1][1];
+            if rising * falling < 0.0 {
+                let maximum = rising > 0.0;
+                if let Some(extremum) = golden_section(calculator, function, triple[0][0], triple[2][0], maximum) {
+                    features.extrema.push(extremum);
+                }
+            }
+        }
+    }
+    features
+}
+
+fn bisect(calculator: &Calculator, function: &UserFunction, mut left: f64, mut right: f64, left_y: f64) -> Option<f64> {
+    for _ in 0..REFINE_STEPS {
+        let middle = (left + right) / 2.0;
+        let y = value_at(calculator, function, middle)?;
+        if y.signum() == left_y.signum() {
+            left = middle;
+        }
+        else {
+            right = middle;
+        }
+    }
+    let root = (left + right) / 2.0;
+    let y = value_at(calculator, function, root)?;
+    (y.abs() <= 1e-6 * (1.0 + left_y.abs())).then_some(root)
+}
+
+fn golden_section(calculator: &Calculator, function: &UserFunction, mut left: f64, mut right: f64, maximum: bool) -> Option<[f64; 2]> {
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let sign = if maximum { -1.0 } else { 1.0 };
+    let objective = |x: f64| value_at(calculator, function, x).map(|y| sign * y);
+    for _ in 0..REFINE_STEPS {
+        let lower = right - ratio * (right - left);
+        let upper = left + ratio * (right - left);
+        if objective(lower)? < objective(upper)? {
+            right = upper;
+        }
+        else {
+            left = lower;
+        }
+    }
+    let x = (left + right) / 2.0;
+    Some([x, value_at(calculator, function, x)?])
+}
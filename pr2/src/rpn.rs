@@ -0,0 +1,158 @@
+use eframe::egui::{self, Grid, Label, RichText, Ui};
+
+use calc_core::{Arity, CalcError, Calculator, DisplayOptions, Span, Value};
+
+const VISIBLE_LEVELS: usize = 4;
+const COUNT_HINT: &str = "min, max, mean and other functions with a variable number of arguments use the top n levels when n is typed first, or the whole stack otherwise";
+
+#[derive(Default)]
+pub(crate) struct RpnStack {
+    values: Vec<Value>,
+}
+
+impl RpnStack {
+    pub(crate) fn x(&self) -> Option<&Value> {
+        self.values.last()
+    }
+
+    pub(crate) fn push(&mut self, value: Value) {
+        self.values.push(value);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    pub(crate) fn command(&mut self, name: &str) -> Result<(), CalcError> {
+        let depth = self.values.len();
+        match name {
+            "dup" => {
+                self.require(name, 1)?;
+                self.values.push(self.values[depth - 1].clone());
+            }
+            "drop" => {
+                self.require(name, 1)?;
+                self.values.pop();
+            }
+            "swap" => {
+                self.require(name, 2)?;
+                self.values.swap(depth - 1, depth - 2);
+            }
+            "roll" => {
+                self.require(name, 2)?;
+                self.values.rotate_right(1);
+            }
+            _ => return Err(CalcError::UnknownName { name: name.to_string(), span: Span { start: 0, end: name.len() } }),
+        }
+        Ok(())
+    }
+
+    pub(crate) fn operate(&mut self, calculator: &Calculator, operator: &str) -> Result<(), CalcError> {
+        self.require(operator, 2)?;
+        let depth = self.values.len();
+        let result = calculator.apply_operator(operator, &self.values[depth - 2], &self.values[depth - 1])?;
+        self.values.truncate(depth - 2);
+        self.values.push(result);
+        Ok(())
+    }
+
+    pub(crate) fn depth(&self) -> usize {
+        self.values.len()
+    }
+
+    pub(crate) fn call(&mut self, calculator: &Calculator, name: &str, arity: Arity, count: usize) -> Result<(), CalcError> {
+        if !arity.accepts(count) {
+            return Err(CalcError::Arity { name: name.to_string(), expected: arity, found: count, span: Span { start: 0, end: name.len() } });
+        }
+        self.require(name, count)?;
+        let start = self.values.len() - count;
+        let result = calculator.apply_function(name, &self.values[start..])?;
+        self.values.truncate(start);
+        self.values.push(result);
+        Ok(())
+    }
+
+    pub(crate) fn negate(&mut self, calculator: &Calculator) -> Result<(), CalcError> {
+        self.require("±", 1)?;
+        let negated = calculator.negate(&self.values[self.values.len() - 1])?;
+        *self.values.last_mut().expect("stack has a value") = negated;
+        Ok(())
+    }
+
+    fn require(&self, name: &str, count: usize) -> Result<(), CalcError> {
+        if self.values.len() >= count {
+            return Ok(());
+        }
+        Err(CalcError::Arity {
+            name: name.to_string(),
+            expected: Arity::Exact(count),
+            found: self.values.len(),
+            span: Span { start: 0, end: name.len() },
+        })
+    }
+
+    pub(crate) fn show(&self, ui: &mut Ui, options: &DisplayOptions) {
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            Grid::new("rpn_stack").num_columns(2).min_col_width(0.0).show(ui, |ui| {
+                for level in (1..=VISIBLE_LEVELS).rev() {
+                    let label = if level == VISIBLE_LEVELS && self.values.len() > VISIBLE_LEVELS {
+                        format!("{}+", level)
+                    }
+                    else {
+                        format!("{}:", level)
+                    };
+                    ui.label(RichText::new(label).small());
+                    let text = self.values.len()
+                        .checked_sub(level)
+                        .map(|index| self.values[index].format(options))
+                        .unwrap_or_default();
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.add(Label::new(RichText::new(text).monospace()).truncate());
+                    });
+                    ui.end_row();
+                }
+            });
+            ui.add(Label::new(RichText::new(COUNT_HINT).small().weak()).wrap());
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use calc_core::{Arity, CalcError, Calculator};
+
+    use super::RpnStack;
+
+    fn stack(calculator: &Calculator, values: &[&str]) -> RpnStack {
+        let mut stack = RpnStack::default();
+        for value in values {
+            stack.push(calculator.evaluate_expression(value).unwrap());
+        }
+        stack
+    }
+
+    #[test]
+    fn aggregates_take_an_explicit_count_or_the_whole_stack() {
+        let calculator = Calculator::default();
+        let arity = calculator.function_arity("max").unwrap();
+        assert_eq!(arity, Arity::AtLeast(1));
+        let mut values = stack(&calculator, &["9", "1", "5", "3"]);
+        values.call(&calculator, "max", arity, 2).unwrap();
+        assert_eq!(values.depth(), 3);
+        assert_eq!(values.x(), Some(&calculator.evaluate_expression("5").unwrap()));
+        values.call(&calculator, "mean", arity, values.depth()).unwrap();
+        assert_eq!(values.depth(), 1);
+        assert_eq!(values.x(), Some(&calculator.evaluate_expression("5").unwrap()));
+    }
+
+    #[test]
+    fn rejects_counts_outside_the_arity() {
+        let calculator = Calculator::default();
+        let mut values = stack(&calculator, &["1", "2", "3", "4", "5"]);
+        let arity = calculator.function_arity("pmt").unwrap();
+        assert!(matches!(values.call(&calculator, "pmt", arity, 5), Err(CalcError::Arity { found: 5, .. })));
+        assert!(matches!(values.call(&calculator, "max", Arity::AtLeast(1), 0), Err(CalcError::Arity { found: 0, .. })));
+        assert!(matches!(values.call(&calculator, "max", Arity::AtLeast(1), 6), Err(CalcError::Arity { found: 5, .. })));
+        assert_eq!(values.depth(), 5);
+    }
+}
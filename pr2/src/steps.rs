@@ -0,0 +1,56 @@
+use eframe::egui::{CollapsingHeader, Color32, Grid, Label, RichText, ScrollArea, Ui};
+
+use calc_core::{CalcError, Calculator, DisplayOptions, Trace, Value};
+
+const STEPS_HEIGHT: f32 = 140.0;
+
+#[derive(Default)]
+pub(crate) struct StepsPanel {
+    open: bool,
+    trace: Option<Result<Trace, CalcError>>,
+}
+
+impl StepsPanel {
+    pub(crate) fn calculate(&mut self, calculator: &mut Calculator, expression: &str) -> Result<Option<Value>, CalcError> {
+        if !self.open {
+            self.trace = None;
+            return calculator.calculate(expression);
+        }
+        let trace = calculator.calculate_traced(expression);
+        let result = trace.as_ref().map(|trace| trace.result.clone()).map_err(CalcError::clone);
+        self.trace = Some(trace);
+        result
+    }
+
+    pub(crate) fn show(&mut self, ui: &mut Ui, options: &DisplayOptions) {
+        let response = CollapsingHeader::new("Steps").id_salt("steps").show(ui, |ui| {
+            match &self.trace {
+                None => {
+                    ui.label(RichText::new("Press = to see how the expression is evaluated").small());
+                }
+                Some(Err(error)) => {
+                    ui.label(RichText::new(error.to_string()).small().color(Color32::RED));
+                }
+                Some(Ok(trace)) => {
+                    Grid::new("trace_forms").num_columns(2).show(ui, |ui| {
+                        for (label, parts) in [("tokens", &trace.tokens), ("postfix", &trace.postfix)] {
+                            ui.label(RichText::new(label).small());
+                            ui.add(Label::new(RichText::new(parts.join(" ")).monospace()).wrap());
+                            ui.end_row();
+                        }
+                    });
+                    ScrollArea::vertical().id_salt("trace_steps").max_height(STEPS_HEIGHT).show(ui, |ui| {
+                        Grid::new("trace_steps").num_columns(2).striped(true).show(ui, |ui| {
+                            for (index, step) in trace.steps.iter().enumerate() {
+                                ui.label(RichText::new(format!("{}.", index + 1)).small());
+                                ui.add(Label::new(RichText::new(step.format(options)).monospace()).wrap());
+                                ui.end_row();
+                            }
+                        });
+                    });
+                }
+            }
+        });
+        self.open = !response.fully_closed();
+    }
+}